@@ -1,4 +1,4 @@
-use std::process::{exit, Command};
+// use std::process::{exit, Command};
 
 fn main() {
     // Run `cargo fmt`
@@ -8,20 +8,24 @@
 pub mod roles;
 mod structs;
 
-use gloo_utils::format::JsValueSerdeExt;
-
 use log::*;
 
+use managment::intel::intel_tick;
+use managment::intents;
 use managment::memory::memory_tick;
-use screeps::{constants::Part, game, prelude::*, CircleStyle, Room, SpawnOptions};
+use managment::rooms::rooms_tick;
+use managment::spawn::spawn_tick;
+use managment::tower::towers_tick;
+use managment::traffic;
+use screeps::{game, prelude::*, CircleStyle};
 
 use structs::visual::{draw_energy, draw_ui};
 use managment::creep::CreepExtend;
-use structs::memory::{GlobalMemory, RoomMemory};
-use structs::{creep::CreepMemory, room::RoomExtend};
+use structs::memory::GlobalMemory;
+use structs::room::RoomExtend;
 use wasm_bindgen::prelude::*;
 
-use crate::{roles::unknown, structs::creep::CreepType};
+use crate::roles::unknown;
 
 mod logging;
 mod managment;
@@ -41,15 +45,16 @@ pub fn game_loop() {
     // mutably borrow the creep_targets refcell, which is holding our creep target locks
     // in the wasm heap
 
+    // what every visible room looks like, routes between rooms are planned from it
+    intel_tick();
+
     for creep in game::creeps().values() {
         creep.run();
         match creep.get_type() {
             Ok(res) => match res {
-                Some(creep_type) => match creep_type {
-                    t => t.run(creep),
-                },
+                Some(creep_type) => creep_type.run(creep.clone()),
                 None => {
-                    unknown::run(creep);
+                    unknown::run(creep.clone());
                 }
             },
             Err(e) => {
@@ -57,35 +62,21 @@ pub fn game_loop() {
             }
         }
 
+        // whatever else the creep can do next to its main action this tick
+        intents::plan_extras(&creep);
+
         // run_creep(&creep, &mut creep_targets);
     }
 
+    // every creep asked where it wants to go, now they get moved without walking into each other
+    traffic::resolve();
+
+    towers_tick();
+
+    rooms_tick();
+
     debug!("running spawns");
-    let mut additional = 0;
-    for spawn in game::spawns().values() {
-        if game::creeps().entries().count() >= 5 {
-            info!("not spawning more creeps ");
-        } else {
-            debug!("running spawn {}", spawn.name());
-            let body = [Part::Move, Part::Move, Part::Carry, Part::Work];
-            if spawn.room().unwrap().energy_available() >= body.iter().map(|p| p.cost()).sum() {
-                // create a unique name, spawn.
-                let name_base = game::time();
-                let name = format!("{}-{}", name_base, additional);
-                let memory = JsValue::from_serde(
-                    &CreepMemory::default()
-                        .set_homeroom(Some(spawn.room().unwrap()))
-                        .set_type(Some(CreepType::Upgrader)),
-                )
-                .unwrap();
-                let opts = SpawnOptions::default().memory(memory);
-                match spawn.spawn_creep_with_options(&body, &name, &opts) {
-                    Ok(()) => additional += 1,
-                    Err(e) => warn!("couldn't spawn: {:?}", e),
-                }
-            }
-        }
-    }
+    spawn_tick();
 
     // memory cleanup; memory gets created for all creeps upon spawning, and any time move_to
     // is used; this should be removed if you're using RawMemory/serde for persistence
@@ -94,11 +85,11 @@ pub fn game_loop() {
     for (_n, r) in my_rooms {
         draw_ui(&r);
 
-        update_room_mem(&r);
-        let best = r.get_best_source().unwrap().pos();
-        let style = CircleStyle::default().fill("red");
-        r.visual()
-            .circle(best.x().0 as f32, best.y().0 as f32, Some(style));
+        if let Some(best) = r.get_best_source(&[]).map(|s| s.pos()) {
+            let style = CircleStyle::default().fill("red");
+            r.visual()
+                .circle(best.x().0 as f32, best.y().0 as f32, Some(style));
+        }
         for source in r.clone().get_sources().iter() {
             draw_energy(source, &r);
         }
@@ -112,20 +103,6 @@ pub fn game_loop() {
     );
     update_stats()
 }
-fn update_room_mem(room: &Room) {
-    let memory = RoomMemory {
-        ..Default::default()
-    };
-    let res = &room.clone().set_memory_obj(memory);
-    match res {
-        Err(e) => {
-            error!("could not set room memory: {e}");
-        }
-        Ok(_) => {
-            trace!("memory set of {}", room.name());
-        }
-    }
-}
 fn update_stats() {
     // get global mem
     let mem = GlobalMemory::get();
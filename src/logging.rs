@@ -67,6 +67,8 @@ impl log::Log for JsNotify {
     fn flush(&self) {}
 }
 
+// there is nothing to log a failed setup to, so it panics instead
+#[allow(clippy::expect_used)]
 pub fn setup_logging(verbosity: log::LevelFilter) {
     fern::Dispatch::new()
         .level(verbosity)
@@ -134,7 +136,7 @@ fn panic_hook(info: &PanicHookInfo<'_>) {
         } else {
             // If there was no `__rust_end_short_backtrace` symbol, use the whole stack
             // but skip the first line, it just says Error.
-            let (_, stack) = stack.split_once('\n').unwrap();
+            let stack = stack.split_once('\n').map_or(stack.as_str(), |(_, s)| s);
             let _ = writeln!(fmt_error, "{}", stack);
         }
     }
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use log::{debug, info};
+use screeps::{find, game, OwnedStructureProperties, Part, ResourceType, Room, RoomName};
+use std::str::FromStr;
+
+use crate::managment::{rebalance::rebalance, repair::with_repair_index};
+use crate::roles;
+use crate::structs::{
+    creep::{CreepMemory, CreepType},
+    memory::RoomMemory,
+    room::RoomExtend,
+    spawn::SpawnRequest,
+};
+
+// recovery spawns go before anything else in the queue
+const RECOVERY_PRIORITY: u32 = 100;
+// census requests that did not get spawned in this many ticks are dropped, the census queues
+// them again with what the room needs by then
+const REQUEST_TTL: u32 = 500;
+
+// the parts of a room's state the population targets are derived from
+#[derive(Debug, Default, Clone)]
+pub struct RoomState {
+    pub sources: u32,
+    pub rcl: u8,
+    pub construction_sites: u32,
+    pub storage_energy: Option<u32>,
+    pub damaged_structures: u32,
+    // hostile creeps in the room that can fight or heal
+    pub hostiles: u32,
+    // the room memory names a room to claim that is not ours yet
+    pub claim: bool,
+}
+
+impl RoomState {
+    pub fn from_room(room: &Room) -> Self {
+        RoomState {
+            sources: room.clone().get_sources().len() as u32,
+            rcl: match room.controller() {
+                Some(c) => c.level(),
+                None => 0,
+            },
+            construction_sites: room.clone().get_construction_sites().len() as u32,
+            storage_energy: room
+                .storage()
+                .map(|s| s.store().get_used_capacity(Some(ResourceType::Energy))),
+            damaged_structures: with_repair_index(room, |index| index.len()) as u32,
+            hostiles: room
+                .find(find::HOSTILE_CREEPS, None)
+                .iter()
+                .filter(|c| {
+                    [Part::Attack, Part::RangedAttack, Part::Heal]
+                        .iter()
+                        .any(|p| c.get_active_bodyparts(*p) > 0)
+                })
+                .count() as u32,
+            claim: claim_target(room).is_some_and(|target| {
+                game::rooms()
+                    .get(target)
+                    .and_then(|r| r.controller())
+                    .is_none_or(|c| !c.my())
+            }),
+        }
+    }
+}
+
+// the room the room's claimers are sent to, set from the console like
+// Memory.rooms.W1N1.claim = "W2N1"
+pub fn claim_target(room: &Room) -> Option<RoomName> {
+    let memory = room.clone().get_memory_obj().ok()?;
+    RoomName::from_str(&memory.claim?).ok()
+}
+
+// how many creeps of every type a room with this state should have
+pub fn desired_population(state: &RoomState) -> HashMap<CreepType, u32> {
+    CreepType::all()
+        .into_iter()
+        .map(|t| {
+            let amount = roles::role(&t).population(state);
+            (t, amount)
+        })
+        .collect()
+}
+
+// recomputes the population targets of a room and queues spawn requests for every type that
+// has fewer creeps alive and queued than it should
+pub fn run_census(room: &Room, memory: &mut RoomMemory) {
+    memory.population = desired_population(&RoomState::from_room(room));
+    for (creep_type, amount) in memory.population_override.iter() {
+        memory.population.insert(creep_type.clone(), *amount);
+    }
+    // creeps that can switch roles cover gaps before new ones get queued for them
+    rebalance(room, memory);
+    if memory.recovery.is_some() {
+        queue_recovery(room, memory);
+        return;
+    }
+
+    let mut wanted: Vec<(CreepType, u32)> = memory
+        .population
+        .iter()
+        .map(|(t, a)| (t.clone(), *a))
+        .collect();
+    wanted.sort_by_key(|(t, _)| std::cmp::Reverse(t.spawn_priority()));
+
+    for (creep_type, target) in wanted {
+        let alive = creep_type
+            .amount_alive(Some(room.clone()))
+            .unwrap_or_default();
+        let queued = memory.spawn_queue.count_role(&creep_type) as u32;
+        if alive + queued >= target {
+            continue;
+        }
+        let body = match creep_type.get_body(room) {
+            Some(b) => b,
+            None => {
+                debug!("room {} can not afford any {creep_type} body", room.name());
+                continue;
+            }
+        };
+        info!(
+            "queueing {creep_type} in {} ({alive} alive, {queued} queued, {target} wanted)",
+            room.name()
+        );
+        let creep_memory = CreepMemory::default().set_homeroom(Some(room.clone()));
+        let priority = creep_type.spawn_priority();
+        memory.spawn_queue.push(
+            SpawnRequest::new(creep_type, body, creep_memory, priority)
+                .set_deadline(Some(game::time() + REQUEST_TTL)),
+        );
+    }
+}
+
+// while recovering the room queues the cheapest gatherers it can build, bigger gatherer
+// requests left in the queue would never become affordable so they are dropped
+fn queue_recovery(room: &Room, memory: &mut RoomMemory) {
+    for creep_type in CreepType::all().into_iter().filter(|t| t.gathers_energy()) {
+        let template = creep_type.body_template();
+        let min_cost = template.min_cost();
+        memory
+            .spawn_queue
+            .requests
+            .retain(|r| r.role != creep_type || r.cost() <= min_cost);
+
+        let target = memory.population.get(&creep_type).copied().unwrap_or(0);
+        let alive = creep_type
+            .amount_alive(Some(room.clone()))
+            .unwrap_or_default();
+        let queued = memory.spawn_queue.count_role(&creep_type) as u32;
+        if alive + queued >= target {
+            continue;
+        }
+        let body = match template.build(min_cost) {
+            Some(b) => b,
+            None => continue,
+        };
+        info!("queueing recovery {creep_type} in {}", room.name());
+        let creep_memory = CreepMemory::default().set_homeroom(Some(room.clone()));
+        memory.spawn_queue.push(
+            SpawnRequest::new(creep_type, body, creep_memory, RECOVERY_PRIORITY)
+                .set_deadline(Some(game::time() + REQUEST_TTL)),
+        );
+    }
+}
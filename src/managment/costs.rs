@@ -0,0 +1,107 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use log::debug;
+use screeps::{
+    find, game, pathfinder::MultiRoomCostResult, HasPosition, LocalCostMatrix,
+    OwnedStructureProperties, Room, RoomName, StructureObject, StructureType,
+};
+
+use crate::managment::danger::add_danger;
+use crate::structs::{
+    costs::{CostLayers, RoomCosts},
+    movement::TerrainCosts,
+};
+
+thread_local! {
+    // lost on a global reset like everything else on the heap, the first search in a room after
+    // that builds its matrices again
+    static COSTS: RefCell<HashMap<RoomName, RoomCosts>> = RefCell::new(HashMap::new());
+}
+
+// the room callback for pathfinder searches, rooms we can not see only get the danger we know
+// of on top of the pathfinder's terrain costs
+pub fn room_costs(
+    room_name: RoomName,
+    layers: CostLayers,
+    costs: TerrainCosts,
+) -> MultiRoomCostResult {
+    let mut matrix = match game::rooms().get(room_name) {
+        Some(room) => with_room_costs(&room, |c| c.matrix(layers, costs.road)),
+        None if layers.danger.is_none() => return MultiRoomCostResult::Default,
+        None => LocalCostMatrix::new(),
+    };
+    if let Some(tolerance) = layers.danger {
+        add_danger(room_name, &mut matrix, tolerance, costs);
+    }
+    MultiRoomCostResult::CostMatrix(matrix.into())
+}
+
+// runs `f` on the cost layers of the room, rebuilding the static layer when the number of
+// structures changed and the creep layer when it is from an earlier tick
+pub fn with_room_costs<F, R>(room: &Room, f: F) -> R
+where
+    F: FnOnce(&RoomCosts) -> R,
+{
+    COSTS.with(|costs| {
+        let mut costs = costs.borrow_mut();
+        let time = game::time();
+        let entry = costs.entry(room.name()).or_default();
+        if !entry.checked.is_current(time) {
+            let structures = room.find(find::STRUCTURES, None);
+            let sites = room.find(find::MY_CONSTRUCTION_SITES, None);
+            let count = structures.len() + sites.len();
+            if entry.structures != Some(count) {
+                debug!("building static cost matrix of {}", room.name());
+                entry.static_layer = static_layer(&structures, &sites);
+                entry.structures = Some(count);
+            }
+            entry.checked.current(time);
+        }
+        entry.creeps.get_or_insert_with(time, || {
+            room.find(find::CREEPS, None)
+                .into_iter()
+                .map(|c| c.pos().xy())
+                .chain(
+                    room.find(find::POWER_CREEPS, None)
+                        .into_iter()
+                        .map(|c| c.pos().xy()),
+                )
+                .collect()
+        });
+        f(entry)
+    })
+}
+
+// roads are cheaper than plains and swamps, anything that can not be walked over is blocked,
+// our own ramparts and containers can be walked over
+fn static_layer(
+    structures: &[StructureObject],
+    sites: &[screeps::ConstructionSite],
+) -> LocalCostMatrix {
+    let mut matrix = LocalCostMatrix::new();
+    for structure in structures {
+        let xy = structure.pos().xy();
+        match structure {
+            StructureObject::StructureRoad(_) => {
+                // a road under something in the way stays blocked
+                if matrix.get(xy) == 0 {
+                    matrix.set(xy, 1);
+                }
+            }
+            StructureObject::StructureContainer(_) => {}
+            StructureObject::StructureRampart(r) => {
+                if !r.my() && !r.is_public() {
+                    matrix.set(xy, 255);
+                }
+            }
+            _ => matrix.set(xy, 255),
+        }
+    }
+    for site in sites {
+        match site.structure_type() {
+            StructureType::Road | StructureType::Container | StructureType::Rampart => {}
+            _ => matrix.set(site.pos().xy(), 255),
+        }
+    }
+    matrix
+}
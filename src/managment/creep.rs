@@ -2,68 +2,24 @@ use anyhow::anyhow;
 use gloo_utils::format::JsValueSerdeExt;
 use log::{debug, error, trace, warn};
 use screeps::{
-    find, game, pathfinder::{self, MultiRoomCostResult, SearchOptions}, CostMatrix, Creep, ErrorCode, HasId, HasPosition, MaybeHasId as _, ResourceType, Room, RoomName, SharedCreepProperties
+    constants::Part, game, Creep, ErrorCode, HasId, HasPosition, RawObjectId, ResourceType, Room, RoomName, SharedCreepProperties
 };
 use serde_json::Error;
 use std::str::FromStr;
 use wasm_bindgen::JsValue;
+use crate::managment::path::travel;
+use crate::managment::reservation::retarget;
+use crate::managment::stuck::check_progress;
+use crate::roles;
+use crate::structs::body::BodyTemplate;
+use crate::structs::movement::MoveOptions;
 use crate::structs::target::CreepTarget;
-use crate::structs::{creep::{CreepMemory, CreepType}, room::RoomExtend as _};
+use crate::structs::task::TaskQueue;
+use crate::structs::creep::{CreepMemory, CreepType};
 
 impl CreepType {
     pub fn run(self, creep: Creep) {
-        match self {
-            CreepType::Builder => {
-                if creep.is_full() {
-                    let construction_sites = creep.room().unwrap().get_construction_sites();
-                    if construction_sites.len() == 0 {
-                    } else {
-                        creep.set_target(Some(CreepTarget::Build(
-                            construction_sites
-                                .first()
-                                .unwrap()
-                                .try_id()
-                                .expect("CANT CONVERT TO id"),
-                        )));
-                    };
-                } else if creep.is_empty() {
-                    creep.set_target(Some(CreepTarget::Harvest(
-                        creep.room().unwrap().get_best_source().unwrap().id(),
-                    )));
-                } else {
-                    // creep.set_target(Some(crate::CreepTarget::Harvest(
-                    //     creep
-                    //         .room()
-                    //         .unwrap().get_best_source().unwrap().id()
-                    // )));
-                }
-            }
-            CreepType::Upgrader => {
-                if creep.is_full() {
-                    let res = creep.set_target(Some(CreepTarget::Upgrade(
-                        creep.room().unwrap().controller().unwrap().id(),
-                    )));
-                    if let Err(err) = res {
-                        error!("error setting creep_target: {err}")
-                    }
-                } else if creep.is_empty() && creep.get_target().unwrap().is_none() {
-                    let res = creep.set_target(Some(CreepTarget::Harvest(
-                        creep.room().unwrap().get_best_source().unwrap().id(),
-                    )));
-                    if let Err(err) = res {
-                        error!("error setting creep_target: {err}")
-                    }
-                } else {
-                    debug!("nothing needs to happen");
-                }
-            }
-            CreepType::Harvester => {
-                let res = creep.say("harvesting", false);
-                if let Err(err) = res {
-                    error!("could not say shit? {err:?}")
-                }
-            }
-        }
+        roles::role(&self).run(creep)
     }
 }
 
@@ -71,19 +27,71 @@ impl CreepType {
 impl CreepType {
     pub fn short_name(&self) -> String {
         match self {
-            CreepType::Builder => format!("bu"),
-            CreepType::Upgrader => format!("up"),
-            CreepType::Harvester => format!("ha"),
+            CreepType::Builder => "bu".to_string(),
+            CreepType::Upgrader => "up".to_string(),
+            CreepType::Harvester => "ha".to_string(),
+            CreepType::Hauler => "hl".to_string(),
+            CreepType::Repairer => "re".to_string(),
+            CreepType::Claimer => "cl".to_string(),
+            CreepType::Defender => "de".to_string(),
         }
     }
 
+    pub fn all() -> Vec<CreepType> {
+        vec![
+            CreepType::Upgrader,
+            CreepType::Builder,
+            CreepType::Harvester,
+            CreepType::Hauler,
+            CreepType::Repairer,
+            CreepType::Claimer,
+            CreepType::Defender,
+        ]
+    }
+
+    pub fn gathers_energy(&self) -> bool {
+        roles::role(self).gathers_energy()
+    }
+
+    pub fn uses_targets(&self) -> bool {
+        roles::role(self).uses_targets()
+    }
+
+    pub fn spawn_priority(&self) -> u32 {
+        roles::role(self).spawn_priority()
+    }
+
+    pub fn move_priority(&self) -> u32 {
+        roles::role(self).move_priority()
+    }
+
+    pub fn danger_tolerance(&self) -> u8 {
+        roles::role(self).danger_tolerance()
+    }
+
+    pub fn body_template(&self) -> BodyTemplate {
+        roles::role(self).body_template()
+    }
+
+    // the biggest body the room can build. when none of this type are alive in the room we
+    // spawn whatever the current energy allows so the room does not wait on a full refill
+    pub fn get_body(&self, room: &Room) -> Option<Vec<Part>> {
+        let template = self.body_template();
+        let alive = self.amount_alive(Some(room.clone())).unwrap_or_default();
+        let energy = match alive {
+            0 => room.energy_available().max(template.min_cost()),
+            _ => room.energy_capacity_available(),
+        };
+        template.build(energy)
+    }
+
     pub fn amount_alive(&self, room: Option<Room>) -> anyhow::Result<u32, anyhow::Error> {
         let creeps = game::creeps().entries().filter(|x| match x.1.get_type() {
             Ok(yay) => match yay {
                 Some(s) => s == *self,
                 None => false,
             },
-            Err(nay) => false,
+            Err(_) => false,
         });
         let res = match room {
             Some(r) => creeps
@@ -99,17 +107,20 @@ impl CreepType {
         }
         .try_into();
         match res {
-            Ok(c) => return Ok(c),
-            Err(e) => return Err(anyhow!(e.to_string())),
+            Ok(c) => Ok(c),
+            Err(e) => Err(anyhow!(e.to_string())),
         }
     }
 }
 
+#[allow(dead_code)]
 pub trait CreepExtend {
     fn get_type(&self) -> Result<Option<CreepType>, Error>;
     fn set_type(&self, new_type: Option<CreepType>) -> Result<(), Error>;
     fn get_target(&self) -> Result<Option<CreepTarget>, Error>;
     fn set_target(&self, new_type: Option<CreepTarget>) -> Result<(), Error>;
+    fn set_tasks(&self, tasks: Vec<CreepTarget>) -> Result<(), Error>;
+    fn next_task(&self) -> Result<Option<CreepTarget>, Error>;
     fn total_of_type(&self, homeroom: bool) -> Result<u32, anyhow::Error>;
     fn get_home_room(&self) -> anyhow::Result<Option<Room>, anyhow::Error>;
     fn has_room(&self) -> bool;
@@ -122,7 +133,12 @@ pub trait CreepExtend {
     fn set_memory_obj(&self, memory: CreepMemory) -> Result<(), Error>;
     fn set_working(&self, working: bool) -> Result<(), Error>;
     fn get_working(&self) -> Result<Option<bool>, Error>;
-    fn b_move<T>(&self, target: T) -> Result<(), ErrorCode>
+    fn get_blacklist(&self) -> Vec<RawObjectId>;
+    fn avoids(&self, id: RawObjectId) -> bool;
+    fn b_move<T>(&self, target: T, options: MoveOptions) -> Result<(), ErrorCode>
+    where
+        T: HasPosition;
+    fn b_move_range<T>(&self, target: T, range: u32) -> Result<(), ErrorCode>
     where
         T: HasPosition;
 }
@@ -137,7 +153,7 @@ impl CreepExtend for Creep {
                 return Err(e);
             }
         };
-        return Ok(creeptype._type);
+        Ok(creeptype._type)
     }
     fn get_home_room(&self) -> anyhow::Result<Option<Room>, anyhow::Error> {
         match self.get_memory_obj() {
@@ -148,19 +164,12 @@ impl CreepExtend for Creep {
                     Some(r) => {
                         let roomname = RoomName::from_str(&r);
                         match roomname {
-                            Err(e) => {
-                                return Err(anyhow!("room not found? {e}"));
-                            }
-                            Ok(o) => {
-                                return Ok(game::rooms().get(o));
-                            }
+                            Err(e) => Err(anyhow!("room not found? {e}")),
+                            Ok(o) => Ok(game::rooms().get(o)),
                         }
-                        // return Ok(game::rooms().get()?);
                     }
-                    None => {
-                        return Err(anyhow!("room not defined"));
-                    }
-                };
+                    None => Err(anyhow!("room not defined")),
+                }
             }
         }
     }
@@ -184,93 +193,142 @@ impl CreepExtend for Creep {
         }
     }
     fn get_memory_obj(&self) -> Result<CreepMemory, Error> {
-        let js_val = &Self::memory(&self);
+        let js_val = &Self::memory(self);
         let js_string: Result<CreepMemory, serde_json::Error> = js_val.into_serde();
         match js_string {
-            Ok(yay) => {
-                return Ok(yay);
-            }
-            Err(e) => {
-                return Err(e);
-            }
+            Ok(yay) => Ok(yay),
+            Err(e) => Err(e),
         }
     }
     fn set_memory_obj(&self, memory: CreepMemory) -> Result<(), Error> {
         let val = JsValue::from_serde(&memory);
         match val {
             Ok(o) => {
-                Self::set_memory(&self, &o);
+                Self::set_memory(self, &o);
 
-                return Ok(());
+                Ok(())
             }
             Err(e) => {
                 error!("error serializing JsValue to CreepMemory: {}", e);
-                return Err(e);
+                Err(e)
             }
         }
     }
     fn set_working(&self, working: bool) -> Result<(), Error> {
         let mem = self.get_memory_obj();
         match mem {
-            Err(e) => {
-                return Err(e);
-            }
+            Err(e) => Err(e),
             Ok(mut o) => {
                 o.working = Some(working);
-                return self.set_memory_obj(o.clone());
+                self.set_memory_obj(o.clone())
             }
         }
     }
     fn get_working(&self) -> Result<Option<bool>, Error> {
         let mem = self.get_memory_obj();
         match mem {
-            Err(e) => {
-                return Err(e);
-            }
-            Ok(o) => {
-                return Ok(o.working);
-            }
+            Err(e) => Err(e),
+            Ok(o) => Ok(o.working),
         }
     }
 
     fn get_target(&self) -> Result<Option<CreepTarget>, Error> {
         let mem = self.get_memory_obj();
         match mem {
-            Err(e) => {
-                return Err(e);
-            }
-            Ok(o) => {
-                return Ok(o.target);
-            }
+            Err(e) => Err(e),
+            Ok(o) => Ok(o.target),
+        }
+    }
+
+    // targets the creep abandoned, roles leave them out when picking a new one. a creep whose
+    // memory can not be read avoids nothing
+    fn get_blacklist(&self) -> Vec<RawObjectId> {
+        match self.get_memory_obj() {
+            Ok(m) => m.blacklisted(game::time()),
+            Err(_) => vec![],
         }
     }
+    fn avoids(&self, id: RawObjectId) -> bool {
+        self.get_blacklist().contains(&id)
+    }
 
     fn set_target(&self, new_target: Option<CreepTarget>) -> Result<(), Error> {
         let mem = self.get_memory_obj();
         match mem {
-            Err(e) => {
-                return Err(e);
-            }
+            Err(e) => Err(e),
             Ok(mut o) => {
+                let time = game::time();
+                let new_target = match new_target {
+                    Some(t) if o.is_blacklisted(t.object_id(), time) => {
+                        debug!("{} will not take abandoned target {t:?}", self.name());
+                        None
+                    }
+                    t => t,
+                };
+                // a target set from outside the queue replaces whatever was queued behind the
+                // old one
+                if o.target != new_target {
+                    o._move = None;
+                    o.tasks.clear();
+                }
+                retarget(self, o.target.as_ref(), new_target.as_ref());
                 o.target = new_target;
-                return self.set_memory_obj(o.clone());
+                self.set_memory_obj(o.clone())
+            }
+        }
+    }
+
+    // makes the first task the creep's target and queues the rest behind it
+    fn set_tasks(&self, tasks: Vec<CreepTarget>) -> Result<(), Error> {
+        let mut memory = self.get_memory_obj()?;
+        let time = game::time();
+        let mut queue = TaskQueue::default();
+        for task in tasks {
+            if memory.is_blacklisted(task.object_id(), time) {
+                debug!("{} will not queue abandoned target {task:?}", self.name());
+                continue;
             }
+            queue.push(task);
         }
+        let next = queue.pop();
+        retarget(self, memory.target.as_ref(), next.as_ref());
+        memory.target = next;
+        memory.tasks = queue;
+        memory._move = None;
+        self.set_memory_obj(memory)
+    }
+
+    // drops the current target for the next queued task, None once the queue is empty
+    fn next_task(&self) -> Result<Option<CreepTarget>, Error> {
+        let mut memory = self.get_memory_obj()?;
+        let next = memory.tasks.pop();
+        retarget(self, memory.target.as_ref(), next.as_ref());
+        memory.target = next.clone();
+        memory._move = None;
+        self.set_memory_obj(memory)?;
+        Ok(next)
     }
 
     fn run(&self) -> bool {
         match self.get_target() {
             Ok(o) => match o {
                 Some(t) => {
-                    match t {
-                        t => t.run(self),
-                    };
+                    if check_progress(self, &t) {
+                        return false;
+                    }
+                    t.run(self);
                 }
                 None => {
-                    let res = self.set_target(Some(CreepTarget::Upgrade(
-                        self.room().unwrap().controller().unwrap().id(),
-                    )));
-                    match res {
+                    if let Ok(Some(t)) = self.get_type() {
+                        if !t.uses_targets() {
+                            return false;
+                        }
+                    }
+                    let controller = match self.room().and_then(|r| r.controller()) {
+                        Some(c) => c,
+                        None => return false,
+                    };
+                    match self.set_target(Some(CreepTarget::Upgrade(controller.id()))) {
                         Err(e) => {
                             error!("could not set target: {e}");
                             return false;
@@ -286,7 +344,7 @@ impl CreepExtend for Creep {
                 warn!("cant get creep target{e:?}")
             }
         }
-        return false;
+        false
     }
 
     fn total_of_type(&self, homeroom: bool) -> anyhow::Result<u32, anyhow::Error> {
@@ -303,7 +361,7 @@ impl CreepExtend for Creep {
             },
             Err(e) => {
                 warn!("creep has no type cant get amount of creeps");
-                return Err(anyhow!("{}", e.to_string()));
+                Err(anyhow!("{}", e.to_string()))
             }
         }
     }
@@ -324,31 +382,17 @@ impl CreepExtend for Creep {
         }
     }
 
-    fn b_move<T>(&self, target: T) -> Result<(), ErrorCode>
+    fn b_move<T>(&self, target: T, options: MoveOptions) -> Result<(), ErrorCode>
     where
         T: HasPosition,
     {
-        let options = SearchOptions::default().room_callback(room_call);
-
-        ///lets find the path
-        let res = pathfinder::search(self.pos(), target.pos(), 1, Some(options));
-        if res.incomplete() {
-            return Err(ErrorCode::NoPath);
-        }
-        self.move_by_path(&res.opaque_path())
+        travel(self, target.pos(), &options)
     }
-}
-fn room_call(room: RoomName) -> MultiRoomCostResult {
-    let room = game::rooms().get(room);
-    if let Some(room) = room {
-        let cost = CostMatrix::new();
-        let creeps = room.find(find::CREEPS, None);
-        for creep in creeps {
-            let pos = creep.pos();
-            cost.set(u8::from(pos.x()), u8::from(pos.y()), 255);
-        }
-        let test = MultiRoomCostResult::CostMatrix(cost);
-        return test;
+
+    fn b_move_range<T>(&self, target: T, range: u32) -> Result<(), ErrorCode>
+    where
+        T: HasPosition,
+    {
+        self.b_move(target, MoveOptions::default().range(range))
     }
-    MultiRoomCostResult::Default
 }
@@ -0,0 +1,231 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use screeps::{game, LocalCostMatrix, RoomName, RoomXY, Terrain};
+
+use crate::managment::{intel::room_intel, tower::tower_damage};
+use crate::structs::{intel::RoomIntel, movement::TerrainCosts};
+
+// keepers attack whatever comes this close to their lair
+const LAIR_RADIUS: u32 = 5;
+const LAIR_DANGER: u32 = 40;
+// attacking hostiles reach this far with ranged attacks and are avoided this long after
+// they were last seen
+const HOSTILE_RADIUS: u32 = 3;
+const HOSTILE_DANGER: u32 = 30;
+const HOSTILE_MEMORY: u32 = 50;
+// a tower's damage at a tile divided by this is the danger it adds there
+const TOWER_DAMAGE_PER_DANGER: u32 = 20;
+// a danger layer is rebuilt this often even when the intel did not change, hostiles seen a
+// while ago stop counting
+const REBUILD_EVERY: u32 = 10;
+
+thread_local! {
+    // the danger layer of every room with the tick it was built and the intel it came from
+    static LAYERS: RefCell<HashMap<RoomName, (u32, RoomIntel, LocalCostMatrix)>> =
+        RefCell::new(HashMap::new());
+}
+
+// raises the cost of every tile that is more dangerous than `tolerance` by how much more
+// dangerous it is, walls and blocked tiles stay as they are. a tile the matrix leaves to the
+// terrain starts from what its terrain costs
+pub fn add_danger(
+    room: RoomName,
+    matrix: &mut LocalCostMatrix,
+    tolerance: u8,
+    costs: TerrainCosts,
+) {
+    let (intel, terrain) = match (room_intel(room), game::map::get_room_terrain(room)) {
+        (Some(i), Some(t)) => (i, t),
+        _ => return,
+    };
+    with_danger_layer(room, intel, |danger| {
+        for (xy, level) in danger.iter() {
+            let terrain = terrain.get(xy.x.u8(), xy.y.u8());
+            if let Some(cost) = raised_cost(matrix.get(xy), terrain, level, tolerance, costs) {
+                matrix.set(xy, cost);
+            }
+        }
+    });
+}
+
+// the cost of a tile `level` dangerous for a creep that tolerates `tolerance`, `current` is what
+// the matrix has for it with 0 leaving it to the terrain. None when the tile stays as it is
+fn raised_cost(
+    current: u8,
+    terrain: Terrain,
+    level: u8,
+    tolerance: u8,
+    costs: TerrainCosts,
+) -> Option<u8> {
+    if level <= tolerance {
+        return None;
+    }
+    let cost = match current {
+        255 => return None,
+        0 => match terrain {
+            Terrain::Wall => return None,
+            Terrain::Swamp => costs.swamp,
+            Terrain::Plain => costs.plain,
+        },
+        c => c,
+    };
+    Some(cost.saturating_add(level - tolerance).min(254))
+}
+
+fn with_danger_layer<F>(room: RoomName, intel: RoomIntel, f: F)
+where
+    F: FnOnce(&LocalCostMatrix),
+{
+    LAYERS.with(|layers| {
+        let mut layers = layers.borrow_mut();
+        let time = game::time();
+        let stale = match layers.get(&room) {
+            Some((built, old, _)) => *old != intel || time >= built + REBUILD_EVERY,
+            None => true,
+        };
+        if stale {
+            let layer = danger_layer(&intel, time);
+            layers.insert(room, (time, intel, layer));
+        }
+        if let Some((_, _, layer)) = layers.get(&room) {
+            f(layer)
+        }
+    })
+}
+
+// how dangerous every tile of the room is from what the intel knows about it
+fn danger_layer(intel: &RoomIntel, time: u32) -> LocalCostMatrix {
+    let mut layer = LocalCostMatrix::new();
+    for (xy, cost) in layer.iter_mut() {
+        *cost = tile_danger(xy, intel, time);
+    }
+    layer
+}
+
+// towers add danger by the damage they do at the tile, lairs and recently seen hostiles add a
+// fixed amount to every tile they reach
+fn tile_danger(xy: RoomXY, intel: &RoomIntel, time: u32) -> u8 {
+    let mut danger: u32 = intel
+        .towers
+        .iter()
+        .map(|t| tower_damage(range(xy, t.xy())) / TOWER_DAMAGE_PER_DANGER)
+        .sum();
+    danger += intel
+        .lairs
+        .iter()
+        .filter(|l| range(xy, l.xy()) <= LAIR_RADIUS)
+        .count() as u32
+        * LAIR_DANGER;
+    if time < intel.hostiles_seen + HOSTILE_MEMORY {
+        danger += intel
+            .hostiles
+            .iter()
+            .filter(|h| range(xy, h.xy()) <= HOSTILE_RADIUS)
+            .count() as u32
+            * HOSTILE_DANGER;
+    }
+    danger.min(254) as u8
+}
+
+fn range(a: RoomXY, b: RoomXY) -> u32 {
+    let dx = (a.x.u8() as i32 - b.x.u8() as i32).unsigned_abs();
+    let dy = (a.y.u8() as i32 - b.y.u8() as i32).unsigned_abs();
+    dx.max(dy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screeps::{Position, RoomCoordinate};
+    use std::str::FromStr;
+
+    fn xy(x: u8, y: u8) -> RoomXY {
+        RoomXY::try_from((x, y)).unwrap()
+    }
+
+    fn pos(x: u8, y: u8) -> Position {
+        Position::new(
+            RoomCoordinate::new(x).unwrap(),
+            RoomCoordinate::new(y).unwrap(),
+            RoomName::from_str("W1N1").unwrap(),
+        )
+    }
+
+    #[test]
+    fn tower_danger_falls_off_with_range() {
+        let intel = RoomIntel {
+            towers: vec![pos(25, 25)],
+            ..Default::default()
+        };
+        // full damage up to range 5, a quarter of it from range 20 on
+        assert_eq!(tile_danger(xy(25, 25), &intel, 0), 30);
+        assert_eq!(tile_danger(xy(30, 25), &intel, 0), 30);
+        let mid = tile_danger(xy(37, 25), &intel, 0);
+        assert!(mid < 30 && mid > 7, "{mid}");
+        assert_eq!(tile_danger(xy(45, 25), &intel, 0), 7);
+        assert_eq!(tile_danger(xy(49, 0), &intel, 0), 7);
+    }
+
+    #[test]
+    fn hostiles_reach_their_range_while_remembered() {
+        let intel = RoomIntel {
+            hostiles: vec![pos(10, 10)],
+            hostiles_seen: 100,
+            ..Default::default()
+        };
+        assert_eq!(tile_danger(xy(13, 7), &intel, 100), HOSTILE_DANGER as u8);
+        assert_eq!(tile_danger(xy(14, 10), &intel, 100), 0);
+        assert_eq!(
+            tile_danger(xy(10, 10), &intel, 100 + HOSTILE_MEMORY - 1),
+            HOSTILE_DANGER as u8
+        );
+        assert_eq!(tile_danger(xy(10, 10), &intel, 100 + HOSTILE_MEMORY), 0);
+    }
+
+    #[test]
+    fn lairs_and_hostiles_add_up() {
+        let intel = RoomIntel {
+            lairs: vec![pos(10, 10)],
+            hostiles: vec![pos(12, 10), pos(14, 10)],
+            hostiles_seen: 0,
+            ..Default::default()
+        };
+        let danger = LAIR_DANGER + 2 * HOSTILE_DANGER;
+        assert_eq!(tile_danger(xy(13, 10), &intel, 0), danger as u8);
+        assert_eq!(tile_danger(xy(18, 10), &intel, 0), 0);
+    }
+
+    #[test]
+    fn danger_is_capped() {
+        let intel = RoomIntel {
+            towers: vec![pos(25, 25); 10],
+            ..Default::default()
+        };
+        assert_eq!(tile_danger(xy(25, 25), &intel, 0), 254);
+    }
+
+    #[test]
+    fn cost_rises_above_the_tolerance() {
+        let costs = TerrainCosts::default();
+        // as dangerous as the creep tolerates leaves the tile alone
+        assert_eq!(raised_cost(0, Terrain::Plain, 20, 20, costs), None);
+        assert_eq!(raised_cost(0, Terrain::Plain, 30, 20, costs), Some(12));
+        assert_eq!(raised_cost(0, Terrain::Swamp, 30, 0, costs), Some(40));
+        // a road or anything else the matrix already has starts from its cost
+        assert_eq!(raised_cost(1, Terrain::Swamp, 30, 20, costs), Some(11));
+    }
+
+    #[test]
+    fn walls_and_blocked_tiles_stay() {
+        let costs = TerrainCosts::default();
+        assert_eq!(raised_cost(0, Terrain::Wall, 100, 0, costs), None);
+        assert_eq!(raised_cost(255, Terrain::Plain, 100, 0, costs), None);
+    }
+
+    #[test]
+    fn cost_is_capped() {
+        let costs = TerrainCosts::default();
+        assert_eq!(raised_cost(250, Terrain::Plain, 100, 0, costs), Some(254));
+        assert_eq!(raised_cost(0, Terrain::Swamp, 254, 0, costs), Some(254));
+    }
+}
@@ -0,0 +1,164 @@
+use std::{cell::RefCell, collections::HashMap, str::FromStr};
+
+use log::{debug, error};
+use screeps::{
+    find, game, HasPosition, OwnedStructureProperties, Part, Position, Room, RoomName,
+    StructureObject,
+};
+
+use crate::structs::{
+    intel::{room_kind, RoomIntel},
+    memory::GlobalMemory,
+    room::RoomExtend,
+};
+
+// intel of a room that did not change is written back to memory this often
+const SAVE_EVERY: u32 = 100;
+
+thread_local! {
+    // the intel of every room we have seen, read from memory once after a global reset
+    static INTEL: RefCell<Option<HashMap<RoomName, RoomIntel>>> = const { RefCell::new(None) };
+}
+
+fn with_intel<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut HashMap<RoomName, RoomIntel>) -> R,
+{
+    INTEL.with(|intel| {
+        let mut intel = intel.borrow_mut();
+        let intel = intel.get_or_insert_with(load_intel);
+        f(intel)
+    })
+}
+
+fn load_intel() -> HashMap<RoomName, RoomIntel> {
+    let rooms = match GlobalMemory::get() {
+        Ok(m) => m.rooms.unwrap_or_default(),
+        Err(e) => {
+            error!("could not read room intel: {e}");
+            return HashMap::new();
+        }
+    };
+    rooms
+        .into_iter()
+        .filter_map(|(name, memory)| Some((RoomName::from_str(&name).ok()?, memory.intel?)))
+        .collect()
+}
+
+// what we know about the room, None when we never saw it
+pub fn room_intel(name: RoomName) -> Option<RoomIntel> {
+    with_intel(|intel| intel.get(&name).cloned())
+}
+
+// whether the danger in any of the rooms changed after `time`
+pub fn danger_changed(rooms: &[RoomName], time: u32) -> bool {
+    with_intel(|intel| {
+        rooms
+            .iter()
+            .any(|r| intel.get(r).is_some_and(|i| i.changed > time))
+    })
+}
+
+// looks at every room we can see and remembers who owns it and what is dangerous in it
+pub fn intel_tick() {
+    let time = game::time();
+    for room in game::rooms().values() {
+        let mut new = observe(&room, time);
+        let save = with_intel(|intel| {
+            // hostiles that left are remembered for a while, they may be right outside
+            if let Some(old) = intel.get(&room.name()) {
+                if new.hostiles.is_empty() {
+                    new.hostiles = old.hostiles.clone();
+                    new.hostiles_seen = old.hostiles_seen;
+                }
+                if new.same_danger(old) {
+                    new.changed = old.changed;
+                }
+            }
+            let save = match intel.get(&room.name()) {
+                Some(old) => {
+                    time >= old.seen + SAVE_EVERY
+                        || RoomIntel {
+                            seen: time,
+                            ..old.clone()
+                        } != new
+                }
+                None => true,
+            };
+            if save {
+                intel.insert(room.name(), new.clone());
+            }
+            save
+        });
+        if !save {
+            continue;
+        }
+        debug!("saving intel of {}", room.name());
+        let mut memory = room.clone().get_memory_obj().unwrap_or_default();
+        memory.intel = Some(new);
+        if let Err(e) = room.clone().set_memory_obj(memory) {
+            error!("could not save intel of {}: {e}", room.name());
+        }
+    }
+}
+
+fn observe(room: &Room, time: u32) -> RoomIntel {
+    let (highway, keeper) = room_kind(room.name());
+    let controller = room.controller();
+    let owner = controller
+        .as_ref()
+        .filter(|c| !c.my())
+        .and_then(|c| c.owner())
+        .map(|o| o.username());
+    let reserved_by = controller
+        .as_ref()
+        .and_then(|c| c.reservation())
+        .map(|r| r.username())
+        .filter(|u| Some(u) != my_username().as_ref());
+    let lairs = room
+        .find(find::STRUCTURES, None)
+        .into_iter()
+        .filter(|s| matches!(s, StructureObject::StructureKeeperLair(_)))
+        .map(|s| s.pos())
+        .collect();
+    let towers = room
+        .find(find::HOSTILE_STRUCTURES, None)
+        .into_iter()
+        .filter(|s| matches!(s, StructureObject::StructureTower(_)))
+        .map(|s| s.pos())
+        .collect();
+    let hostiles: Vec<Position> = room
+        .find(find::HOSTILE_CREEPS, None)
+        .into_iter()
+        .filter(|c| {
+            c.get_active_bodyparts(Part::Attack) > 0
+                || c.get_active_bodyparts(Part::RangedAttack) > 0
+        })
+        .map(|c| c.pos())
+        .collect();
+    let hostiles_seen = match hostiles.is_empty() {
+        true => 0,
+        false => time,
+    };
+    RoomIntel {
+        seen: time,
+        owner,
+        reserved_by,
+        keeper,
+        highway,
+        lairs,
+        towers,
+        hostiles,
+        hostiles_seen,
+        changed: time,
+    }
+}
+
+// the name of the player running this code, read off any of our spawns
+fn my_username() -> Option<String> {
+    game::spawns()
+        .values()
+        .next()
+        .and_then(|s| s.owner())
+        .map(|o| o.username())
+}
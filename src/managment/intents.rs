@@ -0,0 +1,176 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use log::{debug, trace};
+use screeps::{
+    constants::HARVEST_POWER, find, game, Creep, ErrorCode, HasId, HasPosition, Part, ResourceType,
+    SharedCreepProperties, StructureObject,
+};
+
+use crate::managment::creep::CreepExtend;
+use crate::structs::{
+    intent::{Conflict, IntentKind},
+    target::CreepTarget,
+    tick::PerTick,
+};
+
+thread_local! {
+    // the intents every creep issued this tick
+    static ISSUED: RefCell<PerTick<HashMap<String, Vec<IntentKind>>>> =
+        RefCell::new(PerTick::default());
+}
+
+fn with_issued<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut HashMap<String, Vec<IntentKind>>) -> R,
+{
+    ISSUED.with(|issued| f(issued.borrow_mut().current(game::time())))
+}
+
+// what would happen to an intent of this kind if the creep issued it now
+pub fn conflict(creep: &Creep, kind: IntentKind) -> Conflict {
+    with_issued(|issued| match issued.get(&creep.name()) {
+        Some(i) => kind.conflict(i),
+        None => Conflict::None,
+    })
+}
+
+// issues the intent unless one the game would prefer was already issued this tick, None when it
+// was not issued
+pub fn issue<F>(creep: &Creep, kind: IntentKind, action: F) -> Option<Result<(), ErrorCode>>
+where
+    F: FnOnce() -> Result<(), ErrorCode>,
+{
+    match conflict(creep, kind) {
+        Conflict::Blocked(other) => {
+            trace!("{} skips {kind:?}, {other:?} wins this tick", creep.name());
+            return None;
+        }
+        Conflict::Overrides(other) => {
+            debug!("{} {kind:?} takes over from {other:?}", creep.name());
+        }
+        Conflict::None => {}
+    }
+    let res = action();
+    if res.is_ok() {
+        record(creep, kind);
+    }
+    Some(res)
+}
+
+// notes an intent the creep issued without going through `issue`
+pub fn record(creep: &Creep, kind: IntentKind) {
+    with_issued(|issued| issued.entry(creep.name()).or_default().push(kind));
+}
+
+// the actions a creep can do on the side of whatever its target or role had it do this tick:
+// picking up what lies next to it, topping up energy from a store it works next to and
+// emptying into a link or container while it harvests
+pub fn plan_extras(creep: &Creep) {
+    if creep.spawning() {
+        return;
+    }
+    let free = creep.store().get_free_capacity(None).max(0) as u32;
+    if free > 0 {
+        pickup_adjacent(creep);
+    }
+
+    let target = creep.get_target().ok().flatten();
+    let working = matches!(
+        target,
+        Some(CreepTarget::Build(_)) | Some(CreepTarget::Repair(_)) | Some(CreepTarget::Upgrade(_))
+    );
+    let energy = creep.store().get_used_capacity(Some(ResourceType::Energy));
+    let capacity = creep.store().get_capacity(None);
+    if working && energy * 2 < capacity {
+        withdraw_adjacent(creep);
+    }
+
+    let harvesting = with_issued(|issued| {
+        issued
+            .get(&creep.name())
+            .is_some_and(|i| i.contains(&IntentKind::Harvest))
+    });
+    // a miner empties itself before the next harvest would overflow its store
+    let per_tick = creep.get_active_bodyparts(Part::Work) as u32 * HARVEST_POWER;
+    if harvesting && energy > 0 && free < per_tick {
+        transfer_adjacent(creep);
+    }
+}
+
+fn pickup_adjacent(creep: &Creep) {
+    let dropped = creep
+        .pos()
+        .find_in_range(find::DROPPED_RESOURCES, 1)
+        .into_iter()
+        .max_by_key(|r| r.amount());
+    if let Some(resource) = dropped {
+        if let Some(Err(e)) = issue(creep, IntentKind::Pickup, || creep.pickup(&resource)) {
+            debug!("{} could not pick up: {e:?}", creep.name());
+        }
+    }
+}
+
+fn withdraw_adjacent(creep: &Creep) {
+    let store = creep
+        .pos()
+        .find_in_range(find::STRUCTURES, 1)
+        .into_iter()
+        .filter(|s| {
+            matches!(
+                s,
+                StructureObject::StructureContainer(_)
+                    | StructureObject::StructureLink(_)
+                    | StructureObject::StructureStorage(_)
+            )
+        })
+        .find(|s| {
+            s.as_has_store()
+                .is_some_and(|h| h.store().get_used_capacity(Some(ResourceType::Energy)) > 0)
+        });
+    if let Some(structure) = store {
+        if let Some(w) = structure.as_withdrawable() {
+            let res = issue(creep, IntentKind::Withdraw, || {
+                creep.withdraw(w, ResourceType::Energy, None)
+            });
+            if let Some(Err(e)) = res {
+                debug!("{} could not top up: {e:?}", creep.name());
+            }
+        }
+    }
+}
+
+// links go first, they send the energy on without a hauler
+fn transfer_adjacent(creep: &Creep) {
+    let mut stores: Vec<StructureObject> = creep
+        .pos()
+        .find_in_range(find::STRUCTURES, 1)
+        .into_iter()
+        .filter(|s| {
+            matches!(
+                s,
+                StructureObject::StructureLink(_) | StructureObject::StructureContainer(_)
+            )
+        })
+        .filter(|s| {
+            s.as_has_store()
+                .is_some_and(|h| h.store().get_free_capacity(Some(ResourceType::Energy)) > 0)
+        })
+        .collect();
+    stores.sort_by_key(|s| !matches!(s, StructureObject::StructureLink(_)));
+    if let Some(structure) = stores.first() {
+        if let Some(t) = structure.as_transferable() {
+            let res = issue(creep, IntentKind::Transfer, || {
+                creep.transfer(t, ResourceType::Energy, None)
+            });
+            match res {
+                Some(Ok(())) => trace!(
+                    "{} emptied into {}",
+                    creep.name(),
+                    structure.as_structure().id()
+                ),
+                Some(Err(e)) => debug!("{} could not empty: {e:?}", creep.name()),
+                None => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use screeps::{
+    find, game, HasId, HasPosition, HasStore, ResourceType, Room, RoomName, StructureObject,
+};
+
+use crate::managment::creep::CreepExtend;
+use crate::structs::{
+    creep::CreepType,
+    logistics::{LogisticsBoard, Offer, OfferKind, Request, RequestKind},
+    tick::PerTick,
+};
+
+// dropped piles smaller than this are not worth a trip
+const MIN_DROPPED: u32 = 20;
+// towers only ask for energy once they are missing at least this much
+const TOWER_REQUEST_THRESHOLD: u32 = 200;
+
+thread_local! {
+    // the boards are rebuilt once per tick and live on the wasm heap so every hauler of the
+    // room works with, and reserves on, the same board
+    static BOARDS: RefCell<HashMap<RoomName, PerTick<LogisticsBoard>>> =
+        RefCell::new(HashMap::new());
+}
+
+// runs `f` on the logistics board of the room, building it first if it is not from this tick
+pub fn with_board<F, R>(room: &Room, f: F) -> R
+where
+    F: FnOnce(&mut LogisticsBoard) -> R,
+{
+    BOARDS.with(|boards| {
+        let mut boards = boards.borrow_mut();
+        let entry = boards.entry(room.name()).or_default();
+        f(entry.get_or_insert_with(game::time(), || build_board(room)))
+    })
+}
+
+fn build_board(room: &Room) -> LogisticsBoard {
+    let mut board = LogisticsBoard::default();
+    let controller = room.controller().map(|c| c.pos());
+    let sources: Vec<_> = room
+        .find(find::SOURCES, None)
+        .iter()
+        .map(|s| s.pos())
+        .collect();
+
+    for structure in room.find(find::STRUCTURES, None) {
+        match structure {
+            StructureObject::StructureSpawn(s) => board.request(Request {
+                id: s.raw_id(),
+                kind: RequestKind::Spawn,
+                pos: s.pos(),
+                resource: Some(ResourceType::Energy),
+                amount: free_energy(s.store().get_free_capacity(Some(ResourceType::Energy))),
+            }),
+            StructureObject::StructureExtension(s) => board.request(Request {
+                id: s.raw_id(),
+                kind: RequestKind::Extension,
+                pos: s.pos(),
+                resource: Some(ResourceType::Energy),
+                amount: free_energy(s.store().get_free_capacity(Some(ResourceType::Energy))),
+            }),
+            StructureObject::StructureTower(s) => {
+                let free = free_energy(s.store().get_free_capacity(Some(ResourceType::Energy)));
+                if free >= TOWER_REQUEST_THRESHOLD {
+                    board.request(Request {
+                        id: s.raw_id(),
+                        kind: RequestKind::Tower,
+                        pos: s.pos(),
+                        resource: Some(ResourceType::Energy),
+                        amount: free,
+                    });
+                }
+            }
+            StructureObject::StructureContainer(s) => {
+                let near_source = sources.iter().any(|p| p.is_near_to(s.pos()));
+                let near_controller = controller.is_some_and(|p| p.in_range_to(s.pos(), 3));
+                if near_controller && !near_source {
+                    board.request(Request {
+                        id: s.raw_id(),
+                        kind: RequestKind::ControllerContainer,
+                        pos: s.pos(),
+                        resource: Some(ResourceType::Energy),
+                        amount: free_energy(
+                            s.store().get_free_capacity(Some(ResourceType::Energy)),
+                        ),
+                    });
+                } else {
+                    offer_store(&mut board, s.raw_id(), OfferKind::Container, s.pos(), &s);
+                }
+            }
+            StructureObject::StructureLink(s) => board.offer(Offer {
+                id: s.raw_id(),
+                kind: OfferKind::Link,
+                pos: s.pos(),
+                resource: ResourceType::Energy,
+                amount: s.store().get_used_capacity(Some(ResourceType::Energy)),
+            }),
+            StructureObject::StructureStorage(s) => {
+                board.offer(Offer {
+                    id: s.raw_id(),
+                    kind: OfferKind::Storage,
+                    pos: s.pos(),
+                    resource: ResourceType::Energy,
+                    amount: s.store().get_used_capacity(Some(ResourceType::Energy)),
+                });
+                board.request(Request {
+                    id: s.raw_id(),
+                    kind: RequestKind::Storage,
+                    pos: s.pos(),
+                    resource: None,
+                    amount: free_energy(s.store().get_free_capacity(None)),
+                });
+            }
+            _ => {}
+        }
+    }
+    for resource in room.find(find::DROPPED_RESOURCES, None) {
+        if resource.amount() < MIN_DROPPED {
+            continue;
+        }
+        board.offer(Offer {
+            id: resource.raw_id(),
+            kind: OfferKind::Dropped,
+            pos: resource.pos(),
+            resource: resource.resource_type(),
+            amount: resource.amount(),
+        });
+    }
+    for tombstone in room.find(find::TOMBSTONES, None) {
+        let (id, pos) = (tombstone.raw_id(), tombstone.pos());
+        offer_store(&mut board, id, OfferKind::Tombstone, pos, &tombstone);
+    }
+    for ruin in room.find(find::RUINS, None) {
+        let (id, pos) = (ruin.raw_id(), ruin.pos());
+        offer_store(&mut board, id, OfferKind::Ruin, pos, &ruin);
+    }
+
+    // work the haulers are already doing is not up for grabs again
+    for creep in game::creeps().values() {
+        let memory = match creep.get_memory_obj() {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+        if memory._type != Some(CreepType::Hauler) {
+            continue;
+        }
+        if memory.homeroom != Some(room.name().to_string()) {
+            continue;
+        }
+        if let Some(task) = &memory.haul {
+            board.reserve(task);
+        }
+    }
+    board
+}
+
+fn offer_store<T: HasStore>(
+    board: &mut LogisticsBoard,
+    id: screeps::RawObjectId,
+    kind: OfferKind,
+    pos: screeps::Position,
+    object: &T,
+) {
+    let store = object.store();
+    for resource in store.store_types() {
+        board.offer(Offer {
+            id,
+            kind,
+            pos,
+            resource,
+            amount: store.get_used_capacity(Some(resource)),
+        });
+    }
+}
+
+fn free_energy(free: i32) -> u32 {
+    free.max(0) as u32
+}
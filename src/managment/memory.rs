@@ -3,6 +3,11 @@ use log::{info, trace, error};
 use screeps::game;
 use wasm_bindgen::JsCast as _;
 use std::collections::HashSet;
+
+use gloo_utils::format::JsValueSerdeExt;
+
+use crate::roles;
+use crate::structs::creep::CreepMemory;
 pub fn memory_tick() {
     match clean_memory() {
         Err(e) => {
@@ -36,11 +41,22 @@ pub fn clean_memory() -> Result<(), Box<dyn std::error::Error>> {
         // iterate memory creeps
         for creep_name_js in Object::keys(&memory_creeps).iter() {
             // convert to String (after converting to JsString)
-            let creep_name = String::from(creep_name_js.dyn_ref::<JsString>().unwrap());
+            let creep_name = match creep_name_js.dyn_ref::<JsString>() {
+                Some(name) => String::from(name),
+                None => continue,
+            };
 
             // check the HashSet for the creep name, deleting if not alive
             if !alive_creeps.contains(&creep_name) {
                 info!("deleting memory for dead creep {}", creep_name);
+                // let the role clean up after the creep before its memory is gone
+                if let Ok(memory) = Reflect::get(&memory_creeps, &creep_name_js) {
+                    if let Ok(memory) = memory.into_serde::<CreepMemory>() {
+                        if let Some(t) = &memory._type {
+                            roles::role(t).on_death(&creep_name, &memory);
+                        }
+                    }
+                }
                 let _ = Reflect::delete_property(&memory_creeps, &creep_name_js);
             }
         }
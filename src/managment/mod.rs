@@ -1,5 +1,21 @@
+pub mod logistics;
 pub mod memory;
+pub mod path;
+pub mod rebalance;
+pub mod repair;
+pub mod replacement;
+pub mod reservation;
 pub mod rooms;
+pub mod route;
+pub mod census;
+pub mod costs;
 pub mod creep;
+pub mod danger;
+pub mod intel;
+pub mod intents;
+pub mod spawn;
+pub mod stuck;
+pub mod tower;
+pub mod traffic;
 // this contains all the managment functions for the script
 // this includes stuff like memory handeling, room managment, creep spawing, creep logic, etc.
\ No newline at end of file
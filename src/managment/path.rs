@@ -0,0 +1,282 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use log::{error, trace};
+use screeps::{
+    game,
+    pathfinder::{self, MultiRoomCostResult, SearchGoal, SearchOptions},
+    Creep, ErrorCode, HasPosition, Part, Position, SharedCreepProperties,
+};
+
+use crate::managment::{
+    costs::room_costs, creep::CreepExtend, intel::danger_changed, route::route, traffic::submit,
+};
+use crate::structs::{
+    costs::CostLayers,
+    creep::CreepMemory,
+    movement::{MoveOptions, PathCosts, TerrainCosts},
+    path::{direction, serialize_path, CachedPath},
+    tick::PerTick,
+};
+
+// a shared path is trusted this long, structures built in the meantime block it at worst and
+// the creep walking it searches again. danger that changed along it is not waited out
+const SHARED_TTL: u32 = 1_000;
+// shared paths kept before the expired ones get cleaned out
+const SHARED_MAX: usize = 500;
+// ticks a creep may stand in front of its next step before it searches around whatever is there
+const BLOCKED_TICKS: u32 = 2;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PathCounts {
+    // full pathfinder searches and the cpu they took
+    pub searches: u32,
+    pub cpu_searching: f64,
+    // moves taken from a creep's own cached path and searches answered by the shared cache
+    pub reused: u32,
+    pub shared: u32,
+}
+
+#[derive(Default)]
+struct PathCache {
+    counts: PerTick<PathCounts>,
+    // what one search costs on average, to put a number on the searches that were saved
+    average_search: f64,
+    // shared paths by where they go and how the creeps walking them move, a creep joins one
+    // that passes next to it
+    shared: HashMap<(Position, u32, PathCosts), Vec<CachedPath>>,
+}
+
+thread_local! {
+    static CACHE: RefCell<PathCache> = RefCell::new(PathCache::default());
+}
+
+fn with_cache<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut PathCache) -> R,
+{
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        f(&mut cache)
+    })
+}
+
+// this tick's counts and the cpu the cached paths saved compared to searching every time
+pub fn path_counts() -> (PathCounts, f64) {
+    with_cache(|cache| {
+        let counts = cache.counts.get(game::time()).copied().unwrap_or_default();
+        let saved = (counts.reused + counts.shared) as f64 * cache.average_search;
+        (counts, saved)
+    })
+}
+
+// moves the creep one step along its cached path to `dest`, searching a new one when it has
+// none, it is for somewhere else, the creep left it, something is standing in the way or the
+// danger along it changed
+pub fn travel(creep: &Creep, dest: Position, options: &MoveOptions) -> Result<(), ErrorCode> {
+    if !options.flee.is_empty() {
+        return flee(creep, options);
+    }
+    let pos = creep.pos();
+    let range = options.range;
+    let mut memory = match creep.get_memory_obj() {
+        Ok(m) => m,
+        Err(e) => {
+            error!("could not read memory of {}: {e}", creep.name());
+            return Err(ErrorCode::InvalidArgs);
+        }
+    };
+    if pos.in_range_to(dest, range) {
+        if memory.path.take().is_some() {
+            save(creep, memory);
+        }
+        return Ok(());
+    }
+
+    let time = game::time();
+    let costs = options.costs(body_costs(creep), role_tolerance(creep));
+    let cached = match memory.path.take() {
+        Some(mut path) if path.dest == dest && path.range == range => {
+            match path.observe(pos) && path.next().is_some() && !outdated(&path, costs) {
+                true => Some(path),
+                false => None,
+            }
+        }
+        _ => None,
+    };
+    let goals = [(dest, range)];
+    let mut path = match cached {
+        Some(path) if path.blocked < BLOCKED_TICKS => {
+            with_cache(|cache| cache.counts.current(time).reused += 1);
+            path
+        }
+        Some(_) => {
+            trace!("{} is blocked, searching around it", creep.name());
+            let steps = search(pos, &goals, false, options, costs, true)?;
+            CachedPath::new(pos, dest, range, steps, time)
+        }
+        None if !options.ignore_creeps => {
+            let steps = search(pos, &goals, false, options, costs, true)?;
+            CachedPath::new(pos, dest, range, steps, time)
+        }
+        None => shared_path(pos, options, costs, dest)?,
+    };
+
+    let res = match path.next() {
+        Some(d) => submit(creep, d),
+        None => Ok(()),
+    };
+    // a tired creep only waits, not getting anywhere then does not mean it is blocked
+    path.stepped = res.is_ok() && path.next().is_some() && creep.fatigue() == 0;
+    memory.path = Some(path);
+    save(creep, memory);
+    res
+}
+
+// a step away from everything the creep flees from, searched every tick since whatever it
+// runs from moves too
+fn flee(creep: &Creep, options: &MoveOptions) -> Result<(), ErrorCode> {
+    let pos = creep.pos();
+    if options
+        .flee
+        .iter()
+        .all(|(from, range)| !pos.in_range_to(*from, *range))
+    {
+        return Ok(());
+    }
+    let costs = options.costs(body_costs(creep), role_tolerance(creep));
+    let steps = search(pos, &options.flee, true, options, costs, true)?;
+    match steps.chars().next().and_then(direction) {
+        Some(d) => submit(creep, d),
+        None => Ok(()),
+    }
+}
+
+// a path searched around danger is searched again once the danger in one of its rooms changed
+fn outdated(path: &CachedPath, costs: PathCosts) -> bool {
+    costs.danger.is_some() && danger_changed(&path.rooms, path.time)
+}
+
+fn save(creep: &Creep, memory: CreepMemory) {
+    if let Err(e) = creep.set_memory_obj(memory) {
+        error!("could not save memory of {}: {e}", creep.name());
+    }
+}
+
+// creeps without a type keep out of danger like civilians
+fn role_tolerance(creep: &Creep) -> u8 {
+    match creep.get_type() {
+        Ok(Some(t)) => t.danger_tolerance(),
+        _ => 0,
+    }
+}
+
+// the terrain costs that fit how fast the creep moves. carry parts only weigh something while
+// they hold resources
+fn body_costs(creep: &Creep) -> TerrainCosts {
+    let body = creep.body();
+    // broken move parts still do not weigh anything, but they do not move the creep either
+    let moves = body
+        .iter()
+        .filter(|p| p.part() == Part::Move && p.hits() > 0)
+        .count() as u32;
+    let parts: Vec<Part> = body.iter().map(|p| p.part()).collect();
+    let weight = TerrainCosts::weight(&parts, creep.store().get_used_capacity(None));
+    TerrainCosts::for_body(moves, weight)
+}
+
+// the path to a place many creeps walk to, like a source, only gets searched once for creeps
+// that move alike. a creep next to a shared path joins it at the step nearest to it
+fn shared_path(
+    from: Position,
+    options: &MoveOptions,
+    costs: PathCosts,
+    to: Position,
+) -> Result<CachedPath, ErrorCode> {
+    let time = game::time();
+    let key = (to, options.range, costs);
+    let hit = with_cache(|cache| {
+        let path = cache
+            .shared
+            .get(&key)?
+            .iter()
+            .filter(|p| time - p.time < SHARED_TTL && !outdated(p, costs))
+            .find_map(|p| p.join(from))?;
+        cache.counts.current(time).shared += 1;
+        Some(path)
+    });
+    if let Some(path) = hit {
+        return Ok(path);
+    }
+    let steps = search(from, &[(to, options.range)], false, options, costs, false)?;
+    let path = CachedPath::new(from, to, options.range, steps, time);
+    with_cache(|cache| {
+        if cache.shared.values().map(|p| p.len()).sum::<usize>() >= SHARED_MAX {
+            for paths in cache.shared.values_mut() {
+                paths.retain(|p| time - p.time < SHARED_TTL);
+            }
+            cache.shared.retain(|_, p| !p.is_empty());
+        }
+        cache.shared.entry(key).or_default().push(path.clone());
+    });
+    Ok(path)
+}
+
+// a full pathfinder search to the goals or away from them when fleeing, around the creeps in
+// the starting room when `around_creeps`. a search into another room stays inside the rooms of
+// the route there
+fn search(
+    from: Position,
+    goals: &[(Position, u32)],
+    flee: bool,
+    options: &MoveOptions,
+    costs: PathCosts,
+    around_creeps: bool,
+) -> Result<String, ErrorCode> {
+    let start = game::cpu::get_used();
+    let here = from.room_name();
+    let corridor = match (flee, goals.first()) {
+        (false, Some((to, _))) => Some(route(here, to.room_name()).ok_or(ErrorCode::NoPath)?),
+        _ => None,
+    };
+    let max_rooms = match &corridor {
+        Some(c) => c.len().min(16) as u8,
+        None => 16,
+    };
+    let mut search_options = SearchOptions::default()
+        .flee(flee)
+        .plain_cost(costs.terrain.plain)
+        .swamp_cost(costs.terrain.swamp)
+        .max_rooms(options.max_rooms.unwrap_or(max_rooms).min(max_rooms));
+    if let Some(ops) = options.max_ops {
+        search_options = search_options.max_ops(ops);
+    }
+    let search_options = search_options.room_callback(move |room| {
+        if corridor.as_ref().is_some_and(|c| !c.contains(&room)) {
+            return MultiRoomCostResult::Impassable;
+        }
+        match around_creeps && room == here {
+            true => room_costs(room, CostLayers::ALL.danger(costs.danger), costs.terrain),
+            false => room_costs(room, CostLayers::STATIC.danger(costs.danger), costs.terrain),
+        }
+    });
+    let goals = goals
+        .iter()
+        .map(|(pos, range)| SearchGoal::new(*pos, *range));
+    let res = pathfinder::search_many(from, goals, Some(search_options));
+    let used = game::cpu::get_used() - start;
+    with_cache(|cache| {
+        let counts = cache.counts.current(game::time());
+        counts.searches += 1;
+        counts.cpu_searching += used;
+        // the first search after a global reset sets the average
+        cache.average_search = if cache.average_search == 0.0 {
+            used
+        } else {
+            cache.average_search * 0.9 + used * 0.1
+        };
+    });
+    if res.incomplete() && !flee {
+        return Err(ErrorCode::NoPath);
+    }
+    serialize_path(from, &res.path()).ok_or(ErrorCode::NoPath)
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use log::{debug, error, info};
+use screeps::{game, Creep, Part, Room, SharedCreepProperties};
+
+use crate::managment::creep::CreepExtend;
+use crate::structs::{
+    creep::{CreepType, Reassignment},
+    memory::RoomMemory,
+};
+
+// the roles a creep with work and carry parts can move between
+const FLEXIBLE: [CreepType; 3] = [CreepType::Builder, CreepType::Upgrader, CreepType::Hauler];
+// a creep that changed roles keeps its new role at least this long, so a room sitting right on
+// a population boundary does not flip its creeps back and forth every tick
+const REASSIGN_COOLDOWN: u32 = 500;
+// below this many ticks to downgrade the controller gets an upgrader no matter what
+const DOWNGRADE_WARNING: u32 = 5_000;
+// reassignments kept in a creep's memory
+const REASSIGN_HISTORY: usize = 5;
+
+// moves flexible creeps from roles the room has too many of to roles it is short of, before the
+// census queues new creeps for the gap. a room that just placed a lot of construction sites
+// turns upgraders into builders and gets them back once the sites are done.
+pub fn rebalance(room: &Room, memory: &RoomMemory) {
+    let mut creeps: HashMap<CreepType, Vec<Creep>> = HashMap::new();
+    for creep in game::creeps().values() {
+        if creep.spawning() {
+            continue;
+        }
+        match creep.get_home_room() {
+            Ok(Some(home)) if home == *room => {}
+            _ => continue,
+        }
+        if let Ok(Some(t)) = creep.get_type() {
+            if FLEXIBLE.contains(&t) {
+                creeps.entry(t).or_default().push(creep);
+            }
+        }
+    }
+
+    // how many creeps every role has over (positive) or under (negative) its target, spawn
+    // requests already queued count as creeps
+    let balance: HashMap<CreepType, i64> = FLEXIBLE
+        .iter()
+        .map(|t| {
+            let alive = creeps.get(t).map(|c| c.len()).unwrap_or(0) as i64;
+            let queued = memory.spawn_queue.count_role(t) as i64;
+            let target = memory.population.get(t).copied().unwrap_or(0) as i64;
+            (t.clone(), alive + queued - target)
+        })
+        .collect();
+
+    let downgrading = room
+        .controller()
+        .and_then(|c| c.ticks_to_downgrade())
+        .is_some_and(|t| t < DOWNGRADE_WARNING)
+        && creeps
+            .get(&CreepType::Upgrader)
+            .is_none_or(|c| c.is_empty());
+    let plan = match plan(&balance, memory.spawn_queue.is_empty(), downgrading) {
+        Some(p) => p,
+        None => return,
+    };
+
+    // take the best fitting creep out of the first donor that has one to spare
+    for from in plan.donors {
+        let candidate = creeps
+            .get(&from)
+            .into_iter()
+            .flatten()
+            .filter(|c| can_take(c, &plan.to))
+            .max_by_key(|c| fit(c, &plan.to));
+        if let Some(creep) = candidate {
+            reassign(creep, from, plan.to, plan.reason);
+            return;
+        }
+    }
+    debug!(
+        "room {} is short of {}s but has no creep to spare",
+        room.name(),
+        plan.to
+    );
+}
+
+// a reassignment the room wants: the role that gets a creep, the roles that may give one up,
+// best first, and why
+#[derive(Debug, PartialEq)]
+struct Plan {
+    to: CreepType,
+    donors: Vec<CreepType>,
+    reason: String,
+}
+
+// decides which role gets a creep and who gives it up, `balance` is how many creeps every role
+// has over or under its target. a role with a surplus always gives first. a controller about
+// to downgrade takes from any role, and a room with an empty spawn queue takes from less
+// important roles the census then spawns a replacement for
+fn plan(balance: &HashMap<CreepType, i64>, queue_empty: bool, downgrading: bool) -> Option<Plan> {
+    let (to, reason) = if downgrading {
+        (
+            CreepType::Upgrader,
+            "controller is about to downgrade".to_string(),
+        )
+    } else {
+        let to = balance
+            .iter()
+            .filter(|(_, b)| **b < 0)
+            .min_by_key(|(t, b)| (**b, std::cmp::Reverse(t.spawn_priority())))
+            .map(|(t, _)| t.clone())?;
+        let reason = if queue_empty {
+            format!("spawn queue is empty and room is short of {to}s")
+        } else {
+            format!("room is short of {to}s")
+        };
+        (to, reason)
+    };
+
+    let mut donors: Vec<(CreepType, i64)> = balance
+        .iter()
+        .filter(|(t, _)| **t != to)
+        .filter(|(t, b)| {
+            **b > 0
+                || downgrading
+                || (queue_empty && **b == 0 && t.spawn_priority() < to.spawn_priority())
+        })
+        .map(|(t, b)| (t.clone(), *b))
+        .collect();
+    donors.sort_by_key(|(t, b)| (std::cmp::Reverse(*b), t.spawn_priority()));
+    Some(Plan {
+        to,
+        donors: donors.into_iter().map(|(t, _)| t).collect(),
+        reason,
+    })
+}
+
+// a creep that changed roles recently keeps its role until the cooldown is over
+fn cooling_down(reassignments: &[Reassignment], now: u32) -> bool {
+    reassignments
+        .last()
+        .is_some_and(|r| now < r.time + REASSIGN_COOLDOWN)
+}
+
+// whether the creep's body can do the role and it has not changed roles recently
+fn can_take(creep: &Creep, to: &CreepType) -> bool {
+    let memory = match creep.get_memory_obj() {
+        Ok(m) => m,
+        Err(_) => return false,
+    };
+    if memory.replaced == Some(true) {
+        return false;
+    }
+    if cooling_down(&memory.reassignments, game::time()) {
+        return false;
+    }
+    let carry = creep.get_active_bodyparts(Part::Carry);
+    match to {
+        CreepType::Hauler => carry > 0,
+        _ => carry > 0 && creep.get_active_bodyparts(Part::Work) > 0,
+    }
+}
+
+// the part that matters most for the role, more of it makes the creep a better fit
+fn fit(creep: &Creep, to: &CreepType) -> u8 {
+    match to {
+        CreepType::Hauler => creep.get_active_bodyparts(Part::Carry),
+        _ => creep.get_active_bodyparts(Part::Work),
+    }
+}
+
+fn reassign(creep: &Creep, from: CreepType, to: CreepType, reason: String) {
+    let mut memory = match creep.get_memory_obj() {
+        Ok(m) => m,
+        Err(e) => {
+            error!("could not read memory of {}: {e}", creep.name());
+            return;
+        }
+    };
+    info!("{} goes from {from} to {to}: {reason}", creep.name());
+    memory._type = Some(to.clone());
+    // whatever the creep was doing belonged to its old role
+    memory.target = None;
+    memory.tasks.clear();
+    memory.haul = None;
+    memory.reassignments.push(Reassignment {
+        time: game::time(),
+        from,
+        to,
+        reason,
+    });
+    if memory.reassignments.len() > REASSIGN_HISTORY {
+        memory.reassignments.remove(0);
+    }
+    if let Err(e) = creep.set_memory_obj(memory) {
+        error!("could not save memory of {}: {e}", creep.name());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(upgraders: i64, builders: i64, haulers: i64) -> HashMap<CreepType, i64> {
+        HashMap::from([
+            (CreepType::Upgrader, upgraders),
+            (CreepType::Builder, builders),
+            (CreepType::Hauler, haulers),
+        ])
+    }
+
+    #[test]
+    fn balanced_room_keeps_its_creeps() {
+        assert_eq!(plan(&balance(0, 0, 0), false, false), None);
+        assert_eq!(plan(&balance(0, 0, 0), true, false), None);
+        assert_eq!(plan(&balance(2, 1, 0), true, false), None);
+    }
+
+    #[test]
+    fn construction_burst_takes_from_the_biggest_surplus() {
+        let plan = plan(&balance(2, -3, 1), false, false).unwrap();
+        assert_eq!(plan.to, CreepType::Builder);
+        assert_eq!(plan.donors, vec![CreepType::Upgrader, CreepType::Hauler]);
+        assert_eq!(plan.reason, "room is short of builders");
+    }
+
+    #[test]
+    fn most_short_role_goes_first() {
+        assert_eq!(
+            plan(&balance(1, -1, -2), false, false).unwrap().to,
+            CreepType::Hauler
+        );
+        // on a tie the more important role gets the creep
+        assert_eq!(
+            plan(&balance(1, -1, -1), false, false).unwrap().to,
+            CreepType::Hauler
+        );
+    }
+
+    #[test]
+    fn busy_queue_only_takes_a_surplus() {
+        let plan = plan(&balance(0, -1, 0), false, false).unwrap();
+        assert!(plan.donors.is_empty());
+    }
+
+    #[test]
+    fn empty_queue_takes_from_less_important_roles() {
+        let plan = plan(&balance(0, -1, 0), true, false).unwrap();
+        assert_eq!(plan.to, CreepType::Builder);
+        // haulers are more important than builders and keep their creeps
+        assert_eq!(plan.donors, vec![CreepType::Upgrader]);
+        assert_eq!(
+            plan.reason,
+            "spawn queue is empty and room is short of builders"
+        );
+    }
+
+    #[test]
+    fn downgrade_takes_from_any_role() {
+        let plan = plan(&balance(0, 0, 1), false, true).unwrap();
+        assert_eq!(plan.to, CreepType::Upgrader);
+        assert_eq!(plan.donors, vec![CreepType::Hauler, CreepType::Builder]);
+        assert_eq!(plan.reason, "controller is about to downgrade");
+    }
+
+    #[test]
+    fn cooldown_after_a_reassignment() {
+        let history = [Reassignment {
+            time: 1000,
+            from: CreepType::Upgrader,
+            to: CreepType::Builder,
+            reason: String::new(),
+        }];
+        assert!(!cooling_down(&[], 1000));
+        assert!(cooling_down(&history, 1000));
+        assert!(cooling_down(&history, 1000 + REASSIGN_COOLDOWN - 1));
+        assert!(!cooling_down(&history, 1000 + REASSIGN_COOLDOWN));
+    }
+}
@@ -0,0 +1,74 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use screeps::{find, game, HasId, HasPosition, Room, RoomName, StructureObject};
+
+use crate::managment::creep::CreepExtend;
+use crate::structs::{
+    repair::{repair_target, repair_threshold, DamagedStructure, RepairIndex},
+    target::CreepTarget,
+    tick::PerTick,
+};
+
+thread_local! {
+    // rebuilt once per tick, towers and repairers take their work out of the same index
+    static INDEXES: RefCell<HashMap<RoomName, PerTick<RepairIndex>>> =
+        RefCell::new(HashMap::new());
+}
+
+// runs `f` on the repair index of the room, building it first if it is not from this tick
+pub fn with_repair_index<F, R>(room: &Room, f: F) -> R
+where
+    F: FnOnce(&mut RepairIndex) -> R,
+{
+    INDEXES.with(|indexes| {
+        let mut indexes = indexes.borrow_mut();
+        let entry = indexes.entry(room.name()).or_default();
+        f(entry.get_or_insert_with(game::time(), || build_index(room)))
+    })
+}
+
+pub fn room_rcl(room: &Room) -> u8 {
+    match room.controller() {
+        Some(c) => c.level(),
+        None => 0,
+    }
+}
+
+fn build_index(room: &Room) -> RepairIndex {
+    let rcl = room_rcl(room);
+    let mut index = RepairIndex::default();
+    for structure in room.find(find::STRUCTURES, None) {
+        // leave other players' buildings alone
+        if let Some(owned) = structure.as_owned() {
+            if !owned.my() {
+                continue;
+            }
+        }
+        if let StructureObject::StructureController(_) = structure {
+            continue;
+        }
+        let s = structure.as_structure();
+        let hits_max = s.hits_max();
+        if hits_max == 0 {
+            continue;
+        }
+        let structure_type = s.structure_type();
+        if s.hits() >= repair_threshold(structure_type, hits_max, rcl) {
+            continue;
+        }
+        index.push(DamagedStructure {
+            id: s.id(),
+            structure_type,
+            pos: s.pos(),
+            hits: s.hits(),
+            target: repair_target(structure_type, hits_max, rcl),
+        });
+    }
+    // structures a creep is already repairing are taken
+    for creep in game::creeps().values() {
+        if let Ok(Some(CreepTarget::Repair(id))) = creep.get_target() {
+            index.remove(id);
+        }
+    }
+    index
+}
@@ -0,0 +1,121 @@
+use log::{debug, error, info};
+use screeps::{
+    constants::CREEP_SPAWN_TIME,
+    game,
+    pathfinder::{self, SearchOptions},
+    Creep, HasPosition, Part, Room, SharedCreepProperties,
+};
+
+use crate::managment::creep::CreepExtend;
+use crate::structs::{memory::RoomMemory, room::RoomExtend, spawn::SpawnRequest};
+
+// creeps with more ticks left than this are never close to needing a successor, so we skip the
+// pathfinding for them
+const MAX_LEAD_TIME: u32 = 300;
+
+// queues a successor for every creep of the room that will die before a new creep could be
+// spawned and walked to its work site
+pub fn queue_replacements(room: &Room, memory: &mut RoomMemory) {
+    for creep in game::creeps().values() {
+        if creep.spawning() {
+            continue;
+        }
+        let ttl = match creep.ticks_to_live() {
+            Some(t) => t,
+            None => continue,
+        };
+        if ttl > MAX_LEAD_TIME {
+            continue;
+        }
+        match creep.get_home_room() {
+            Ok(Some(home)) if home == *room => {}
+            _ => continue,
+        }
+        let mut creep_memory = match creep.get_memory_obj() {
+            Ok(o) => o,
+            Err(e) => {
+                error!("could not read memory of {}: {e}", creep.name());
+                continue;
+            }
+        };
+        if creep_memory.replaced.unwrap_or(false) {
+            continue;
+        }
+        let role = match creep_memory._type.clone() {
+            Some(t) => t,
+            None => continue,
+        };
+        let body = match role.get_body(room) {
+            Some(b) => b,
+            None => creep.body().iter().map(|p| p.part()).collect(),
+        };
+        let travel_known = creep_memory.travel_time.is_some();
+        let travel = match creep_memory.travel_time {
+            Some(t) => t,
+            None => {
+                let t = travel_time(&creep, room);
+                creep_memory.travel_time = Some(t);
+                t
+            }
+        };
+        let lead = body.len() as u32 * CREEP_SPAWN_TIME + travel;
+        if ttl > lead {
+            // only write back when the travel time was just worked out
+            if travel_known {
+                continue;
+            }
+            if let Err(e) = creep.set_memory_obj(creep_memory) {
+                error!("could not save memory of {}: {e}", creep.name());
+            }
+            continue;
+        }
+
+        info!(
+            "queueing replacement for {} ({ttl} ticks left, {lead} lead time)",
+            creep.name()
+        );
+        let priority = role.spawn_priority();
+        memory.spawn_queue.push(
+            SpawnRequest::new(role, body, creep_memory.successor(), priority)
+                .set_deadline(Some(game::time() + ttl)),
+        );
+        creep_memory.replaced = Some(true);
+        if let Err(e) = creep.set_memory_obj(creep_memory) {
+            error!("could not save memory of {}: {e}", creep.name());
+        }
+    }
+}
+
+// path length from the closest spawn to where the creep works, counted with the speed of the
+// creep's body
+fn travel_time(creep: &Creep, room: &Room) -> u32 {
+    let site = match creep.get_target() {
+        Ok(Some(target)) => target.pos().unwrap_or(creep.pos()),
+        _ => creep.pos(),
+    };
+    let spawn = room
+        .clone()
+        .get_spawn()
+        .into_iter()
+        .min_by_key(|s| s.pos().get_range_to(site));
+    let spawn = match spawn {
+        Some(s) => s,
+        None => return 0,
+    };
+    let res = pathfinder::search(spawn.pos(), site, 1, Some(SearchOptions::default()));
+    let steps = res.path().len() as u32;
+    // every non move part slows the creep down on plains
+    let parts = creep.body();
+    let moves = parts.iter().filter(|p| p.part() == Part::Move).count() as u32;
+    let others = parts.len() as u32 - moves;
+    let ticks_per_step = match moves {
+        0 => 1,
+        m => others.div_ceil(m).max(1),
+    };
+    debug!(
+        "travel time of {} is {} ticks",
+        creep.name(),
+        steps * ticks_per_step
+    );
+    steps * ticks_per_step
+}
@@ -0,0 +1,86 @@
+use std::cell::RefCell;
+
+use log::debug;
+use screeps::{
+    constants::{BUILD_POWER, HARVEST_POWER, REPAIR_POWER},
+    game, Creep, Part, RawObjectId, ResourceType,
+};
+
+use crate::managment::creep::CreepExtend;
+use crate::structs::{creep::CreepMemory, reservation::Ledger, target::CreepTarget, tick::PerTick};
+
+thread_local! {
+    // lives on the heap for one tick, after a global reset it is rebuilt from creep memory
+    static LEDGER: RefCell<PerTick<Ledger>> = RefCell::new(PerTick::default());
+}
+
+// runs `f` on this tick's reservation ledger, building it from every creep's memory first if
+// it is from an earlier tick
+pub fn with_ledger<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut Ledger) -> R,
+{
+    LEDGER.with(|ledger| {
+        let mut ledger = ledger.borrow_mut();
+        f(ledger.get_or_insert_with(game::time(), build_ledger))
+    })
+}
+
+fn build_ledger() -> Ledger {
+    let mut ledger = Ledger::default();
+    for creep in game::creeps().values() {
+        let memory = match creep.get_memory_obj() {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+        for (id, amount) in claims(&creep, &memory) {
+            ledger.reserve(id, amount);
+        }
+    }
+    debug!("reservation ledger has {} objects", ledger.reserved.len());
+    ledger
+}
+
+// everything a creep with this memory holds a claim on
+fn claims(creep: &Creep, memory: &CreepMemory) -> Vec<(RawObjectId, u32)> {
+    let mut claims = vec![];
+    if let Some(target) = &memory.target {
+        claims.push((target.object_id(), claim(creep, target)));
+    }
+    // static miners hold on to their source without a target
+    if let (Some(source), None) = (memory.source, &memory.target) {
+        let work = creep.get_active_bodyparts(Part::Work) as u32;
+        claims.push((source.into(), work * HARVEST_POWER));
+    }
+    claims
+}
+
+// how much of the target the creep claims: the work it puts into sources and sites, the space
+// it has for a pickup or the resources it brings to a transfer
+pub fn claim(creep: &Creep, target: &CreepTarget) -> u32 {
+    let work = creep.get_active_bodyparts(Part::Work) as u32;
+    let store = creep.store();
+    match target {
+        CreepTarget::Harvest(_) => work * HARVEST_POWER,
+        CreepTarget::Build(_) => work * BUILD_POWER,
+        CreepTarget::Repair(_) => work * REPAIR_POWER,
+        CreepTarget::Upgrade(_) => work,
+        CreepTarget::Withdraw(_, _) | CreepTarget::Pickup(_) => {
+            store.get_free_capacity(None).max(0) as u32
+        }
+        CreepTarget::Transfer(_, resource) => store.get_used_capacity(Some(*resource)),
+        CreepTarget::Spawn(_) => store.get_used_capacity(Some(ResourceType::Energy)),
+    }
+}
+
+// moves a creep's claim from its old target to its new one
+pub fn retarget(creep: &Creep, old: Option<&CreepTarget>, new: Option<&CreepTarget>) {
+    with_ledger(|ledger| {
+        if let Some(t) = old {
+            ledger.release(t.object_id(), claim(creep, t));
+        }
+        if let Some(t) = new {
+            ledger.reserve(t.object_id(), claim(creep, t));
+        }
+    })
+}
@@ -1,5 +1,61 @@
+use log::{error, trace, warn};
+use screeps::{game, HasId, Room};
 
+use crate::managment::{census::run_census, replacement::queue_replacements};
+use crate::structs::{creep::CreepType, memory::RoomMemory, room::RoomExtend};
 
 pub fn rooms_tick() {
-    
-}
\ No newline at end of file
+    let my_rooms = game::rooms().values().filter(|r| r.is_mine());
+    for room in my_rooms {
+        let mut memory = room.clone().get_memory_obj().unwrap_or_default();
+        update_room_mem(&room, &mut memory);
+        check_recovery(&room, &mut memory);
+        queue_replacements(&room, &mut memory);
+        run_census(&room, &mut memory);
+        let res = room.clone().set_memory_obj(memory);
+        match res {
+            Err(e) => {
+                error!("could not set room memory: {e}");
+            }
+            Ok(_) => {
+                trace!("memory set of {}", room.name());
+            }
+        }
+    }
+}
+
+fn update_room_mem(room: &Room, memory: &mut RoomMemory) {
+    memory.sources = room.clone().get_sources().iter().map(|s| s.id()).collect();
+    memory.controller = room.get_controller_id();
+}
+
+// a room without any creeps that gather energy can only rebuild from what is left in its
+// spawns and extensions, so it goes into recovery until it has a gatherer per source again
+fn check_recovery(room: &Room, memory: &mut RoomMemory) {
+    let gatherers: u32 = CreepType::all()
+        .iter()
+        .filter(|t| t.gathers_energy())
+        .map(|t| t.amount_alive(Some(room.clone())).unwrap_or_default())
+        .sum();
+    match memory.recovery {
+        None => {
+            if gatherers == 0 {
+                warn!(
+                    "room {} has no energy gatherers left, entering recovery mode",
+                    room.name()
+                );
+                memory.recovery = Some(game::time());
+            }
+        }
+        Some(since) => {
+            if gatherers >= (memory.sources.len() as u32).max(1) {
+                warn!(
+                    "room {} recovered after {} ticks",
+                    room.name(),
+                    game::time() - since
+                );
+                memory.recovery = None;
+            }
+        }
+    }
+}
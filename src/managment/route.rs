@@ -0,0 +1,75 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use log::{debug, warn};
+use screeps::{
+    game::{self, map::FindRouteOptions},
+    RoomName,
+};
+
+use crate::managment::intel::room_intel;
+use crate::structs::intel::room_kind;
+
+// a route is searched again after this long, intel about the rooms on it may have changed
+const ROUTE_TTL: u32 = 1_500;
+
+// the rooms between two rooms, None when there is no way through
+type Route = Option<Vec<RoomName>>;
+
+thread_local! {
+    // keyed by the rooms the route goes from and to, with the tick it was searched
+    static ROUTES: RefCell<HashMap<(RoomName, RoomName), (u32, Route)>> =
+        RefCell::new(HashMap::new());
+}
+
+// the rooms a creep walks through from `from` to `to`, both included. rooms owned by other
+// players are never entered, reserved and source keeper rooms only when there is no other way
+// and highways are preferred
+pub fn route(from: RoomName, to: RoomName) -> Option<Vec<RoomName>> {
+    if from == to {
+        return Some(vec![from]);
+    }
+    let time = game::time();
+    let cached = ROUTES.with(|routes| match routes.borrow().get(&(from, to)) {
+        Some((created, rooms)) if time < created + ROUTE_TTL => Some(rooms.clone()),
+        _ => None,
+    });
+    if let Some(rooms) = cached {
+        return rooms;
+    }
+
+    let options = FindRouteOptions::new().room_callback(|to_room, _from_room| room_cost(to_room));
+    let rooms = match game::map::find_route(from, to, Some(options)) {
+        Ok(steps) => {
+            let rooms: Vec<RoomName> = std::iter::once(from)
+                .chain(steps.into_iter().map(|s| s.room))
+                .collect();
+            debug!(
+                "route from {from} to {to} goes through {} rooms",
+                rooms.len()
+            );
+            Some(rooms)
+        }
+        Err(e) => {
+            warn!("no route from {from} to {to}: {e:?}");
+            None
+        }
+    };
+    ROUTES.with(|routes| {
+        let mut routes = routes.borrow_mut();
+        routes.retain(|_, (created, _)| time < *created + ROUTE_TTL);
+        routes.insert((from, to), (time, rooms.clone()));
+    });
+    rooms
+}
+
+// what entering the room costs the route, infinity keeps the route out
+fn room_cost(room: RoomName) -> f64 {
+    let (highway, keeper) = room_kind(room);
+    match room_intel(room) {
+        Some(intel) if intel.hostile() => f64::INFINITY,
+        Some(intel) if intel.reserved_by.is_some() => 5.0,
+        _ if keeper => 10.0,
+        _ if highway => 1.0,
+        _ => 2.5,
+    }
+}
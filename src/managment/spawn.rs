@@ -0,0 +1,104 @@
+use gloo_utils::format::JsValueSerdeExt;
+use log::{debug, error, info, warn};
+use screeps::{game, ErrorCode, Room, SpawnOptions};
+use wasm_bindgen::JsValue;
+
+use crate::roles;
+use crate::structs::{memory::RoomMemory, room::RoomExtend};
+
+// drains the spawn queue of every owned room, one request per idle spawn
+pub fn spawn_tick() {
+    let my_rooms = game::rooms().values().filter(|r| r.is_mine());
+    for room in my_rooms {
+        let mut memory = match room.clone().get_memory_obj() {
+            Ok(o) => o,
+            Err(e) => {
+                error!("could not read room memory of {}: {e}", room.name());
+                continue;
+            }
+        };
+        let expired = memory.spawn_queue.prune_expired(game::time());
+        if expired > 0 {
+            info!(
+                "dropped {expired} expired spawn requests in {}",
+                room.name()
+            );
+        }
+        let (rebuilt, dropped) = memory
+            .spawn_queue
+            .fit(room.energy_capacity_available(), |role, energy| {
+                role.body_template().build(energy)
+            });
+        if rebuilt + dropped > 0 {
+            info!(
+                "{rebuilt} spawn requests in {} rebuilt and {dropped} dropped for its capacity",
+                room.name()
+            );
+        }
+        run_spawns(&room, &mut memory);
+        if let Err(e) = room.clone().set_memory_obj(memory) {
+            error!("could not set room memory: {e}");
+        }
+    }
+}
+
+fn run_spawns(room: &Room, memory: &mut RoomMemory) {
+    let mut additional = 0;
+    // the room's energy only shows what earlier spawns this tick used up next tick
+    let mut energy = room.energy_available();
+    for spawn in room.clone().get_spawn() {
+        if spawn.spawning().is_some() {
+            continue;
+        }
+        // while recovering only the creeps that bring energy in get spawned, everything else
+        // waits in the queue until income is back
+        let recovering = memory.recovery.is_some();
+        let index = match memory
+            .spawn_queue
+            .find(|r| !recovering || r.role.gathers_energy())
+        {
+            Some(i) => i,
+            None => {
+                debug!("nothing to spawn in {}", room.name());
+                return;
+            }
+        };
+        let mut request = memory.spawn_queue.requests[index].clone();
+        // wait for the energy of the most important request instead of letting cheaper ones
+        // jump ahead of it
+        if energy < request.cost() {
+            debug!(
+                "not enough energy for {} in {} ({energy}/{})",
+                request.role,
+                room.name(),
+                request.cost()
+            );
+            return;
+        }
+        debug!("running spawn {}", spawn.name());
+        roles::role(&request.role).on_spawn(room, &mut request.memory);
+        let memory_val = match JsValue::from_serde(&request.memory) {
+            Ok(o) => o,
+            Err(e) => {
+                error!("could not serialize creep memory for spawning: {e}");
+                memory.spawn_queue.remove(index);
+                continue;
+            }
+        };
+        let name = format!("{}-{}", game::time(), additional);
+        let opts = SpawnOptions::default().memory(memory_val);
+        match spawn.spawn_creep_with_options(&request.body, &name, &opts) {
+            Ok(()) => {
+                additional += 1;
+                energy -= request.cost();
+                memory.spawn_queue.remove(index);
+                info!("spawning {} {name} in {}", request.role, room.name());
+            }
+            Err(ErrorCode::InvalidArgs) => {
+                warn!("dropping invalid spawn request for {}", request.role);
+                memory.spawn_queue.remove(index);
+            }
+            Err(e) => debug!("{} could not spawn: {e:?}", spawn.name()),
+        }
+    }
+}
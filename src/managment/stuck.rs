@@ -0,0 +1,212 @@
+use std::cell::RefCell;
+
+use log::{error, info};
+use screeps::{game, Creep, HasPosition, Position, SharedCreepProperties};
+
+use crate::managment::{creep::CreepExtend, reservation::retarget};
+use crate::structs::{
+    creep::CreepMemoryMove,
+    room::RoomExtend,
+    target::{CreepTarget, Limits, TargetLimits},
+    tick::PerTick,
+};
+
+// how long a creep stays away from a target it abandoned
+const BLACKLIST_TICKS: u32 = 200;
+// idle ticks before a creep shows up as stuck in the stats
+const STUCK_AFTER: u32 = 5;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StuckCounts {
+    pub stuck: u32,
+    pub abandoned: u32,
+}
+
+thread_local! {
+    static COUNTS: RefCell<PerTick<StuckCounts>> = RefCell::new(PerTick::default());
+}
+
+fn count<F>(f: F)
+where
+    F: FnOnce(&mut StuckCounts),
+{
+    COUNTS.with(|counts| f(counts.borrow_mut().current(game::time())))
+}
+
+// the counts of this tick
+pub fn stuck_counts() -> StuckCounts {
+    COUNTS.with(|counts| {
+        counts
+            .borrow()
+            .get(game::time())
+            .copied()
+            .unwrap_or_default()
+    })
+}
+
+// the limits set in the memory of the creep's home room
+fn room_limits(creep: &Creep) -> TargetLimits {
+    match creep.get_home_room() {
+        Ok(Some(room)) => room
+            .get_memory_obj()
+            .map(|m| m.target_limits)
+            .unwrap_or_default(),
+        _ => TargetLimits::default(),
+    }
+}
+
+// what `check_progress` sees of a creep this tick
+#[derive(Debug, Clone, Copy)]
+pub struct Observation {
+    pub spawning: bool,
+    pub pos: Position,
+    pub load: u32,
+    // the creep stands next to the empty source it harvests and waits for it to regenerate
+    pub waiting: bool,
+}
+
+impl Observation {
+    fn of(creep: &Creep, target: &CreepTarget) -> Self {
+        let waiting = match target {
+            CreepTarget::Harvest(id) => id
+                .resolve()
+                .is_some_and(|s| s.energy() == 0 && creep.pos().is_near_to(s.pos())),
+            _ => false,
+        };
+        Observation {
+            spawning: creep.spawning(),
+            pos: creep.pos(),
+            load: creep.store().get_used_capacity(None),
+            waiting,
+        }
+    }
+}
+
+// records how the creep is doing on its target and drops the target once the creep spent too
+// long on it or stopped making progress. returns true when the target was abandoned.
+pub fn check_progress(creep: &Creep, target: &CreepTarget) -> bool {
+    let mut memory = match creep.get_memory_obj() {
+        Ok(m) => m,
+        Err(e) => {
+            error!("could not read memory of {}: {e}", creep.name());
+            return false;
+        }
+    };
+    let time = game::time();
+    let seen = Observation::of(creep, target);
+    let limits = target.limits(&room_limits(creep));
+    let reason = progress(&mut memory._move, target.pos(), &seen, limits, time);
+    if memory._move.as_ref().is_some_and(|t| t.idle >= STUCK_AFTER) {
+        count(|c| c.stuck += 1);
+    }
+    let abandoned = match reason {
+        Some(reason) => {
+            info!("{} abandons {target:?}: {reason}", creep.name());
+            memory.blacklist(target.object_id(), time, BLACKLIST_TICKS);
+            let next = memory.tasks.pop();
+            retarget(creep, Some(target), next.as_ref());
+            memory.target = next;
+            memory._move = None;
+            count(|c| c.abandoned += 1);
+            true
+        }
+        None => false,
+    };
+    if let Err(e) = creep.set_memory_obj(memory) {
+        error!("could not save memory of {}: {e}", creep.name());
+    }
+    abandoned
+}
+
+// updates the tracker with what was seen this tick and says why the creep should give up on its
+// target, None while it is doing fine. the clock only starts once the creep is spawned, a
+// successor inherits its target long before it can work on it
+fn progress(
+    tracker: &mut Option<CreepMemoryMove>,
+    dest: Option<Position>,
+    seen: &Observation,
+    limits: Limits,
+    time: u32,
+) -> Option<String> {
+    if seen.spawning {
+        *tracker = None;
+        return None;
+    }
+    let tracker = tracker.get_or_insert_with(|| CreepMemoryMove::new(time, dest));
+    tracker.observe(seen.pos, seen.load, seen.waiting);
+    if time.saturating_sub(tracker.time) > limits.max_ticks {
+        Some(format!("spent {} ticks on it", limits.max_ticks))
+    } else if tracker.idle > limits.max_idle {
+        Some(format!("made no progress for {} ticks", limits.max_idle))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screeps::{RoomCoordinate, RoomName};
+    use std::str::FromStr;
+
+    const LIMITS: Limits = Limits {
+        max_ticks: 300,
+        max_idle: 25,
+    };
+
+    fn seen(spawning: bool, waiting: bool) -> Observation {
+        Observation {
+            spawning,
+            pos: Position::new(
+                RoomCoordinate::new(10).unwrap(),
+                RoomCoordinate::new(10).unwrap(),
+                RoomName::from_str("W1N1").unwrap(),
+            ),
+            load: 0,
+            waiting,
+        }
+    }
+
+    #[test]
+    fn spawning_does_not_count() {
+        let mut tracker = None;
+        for time in 100..200 {
+            assert_eq!(
+                progress(&mut tracker, None, &seen(true, false), LIMITS, time),
+                None
+            );
+            assert!(tracker.is_none());
+        }
+        assert_eq!(
+            progress(&mut tracker, None, &seen(false, false), LIMITS, 200),
+            None
+        );
+        assert_eq!(tracker.as_ref().map(|t| t.time), Some(200));
+    }
+
+    #[test]
+    fn standing_still_is_no_progress() {
+        let mut tracker = None;
+        let reasons: Vec<_> = (0..=26)
+            .filter_map(|time| progress(&mut tracker, None, &seen(false, false), LIMITS, time))
+            .collect();
+        assert_eq!(reasons.len(), 1);
+    }
+
+    #[test]
+    fn waiting_for_a_source_is_not_idling() {
+        let mut tracker = None;
+        for time in 0..1_000 {
+            assert_eq!(
+                progress(&mut tracker, None, &seen(false, true), LIMITS, time),
+                None
+            );
+        }
+        assert_eq!(tracker.as_ref().map(|t| t.idle), Some(0));
+        // once the source is back the clock goes on where it was
+        assert_eq!(
+            progress(&mut tracker, None, &seen(false, false), LIMITS, 1_000),
+            None
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use log::{debug, info};
+use screeps::{
+    constants::{
+        ATTACK_POWER, DISMANTLE_POWER, HEAL_POWER, RANGED_ATTACK_POWER, RANGED_HEAL_POWER,
+        TOWER_ENERGY_COST, TOWER_FALLOFF, TOWER_FALLOFF_RANGE, TOWER_OPTIMAL_RANGE,
+        TOWER_POWER_ATTACK,
+    },
+    find, game, Creep, HasPosition, Part, ResourceType, Room, SharedCreepProperties,
+    StructureObject, StructureTower,
+};
+
+use crate::managment::repair::with_repair_index;
+use crate::structs::{room::RoomExtend, visual::draw_tower_action};
+
+// towers only repair with what is left over above this much energy in the tower
+const TOWER_REPAIR_RESERVE: u32 = 500;
+// and only when the storage (if the room has one) is above this
+const STORAGE_REPAIR_RESERVE: u32 = 10_000;
+// a claim part has no power of its own, but a creep that can attack our controller is worth
+// shooting before the damage dealers
+const CLAIM_DANGER: u32 = 100;
+
+pub fn towers_tick() {
+    let my_rooms = game::rooms().values().filter(|r| r.is_mine());
+    for room in my_rooms {
+        run_towers(&room);
+    }
+}
+
+// towers without the energy for a single action sit this tick out
+fn run_towers(room: &Room) {
+    let towers: Vec<StructureTower> = room
+        .find(find::MY_STRUCTURES, None)
+        .into_iter()
+        .filter_map(|s| match s {
+            StructureObject::StructureTower(t) => Some(t),
+            _ => None,
+        })
+        .filter(|t| t.store().get_used_capacity(Some(ResourceType::Energy)) >= TOWER_ENERGY_COST)
+        .collect();
+    if towers.is_empty() {
+        return;
+    }
+
+    let hostiles = room.find(find::HOSTILE_CREEPS, None);
+    if let Some(target) = pick_hostile(&towers, &hostiles) {
+        info!(
+            "towers in {} focusing {} of {}",
+            room.name(),
+            target.name(),
+            target.owner().username()
+        );
+        for tower in towers.iter() {
+            if let Err(e) = tower.attack(&target) {
+                debug!("tower could not attack: {e:?}");
+                continue;
+            }
+            draw_tower_action(room, tower.pos(), target.pos(), "red");
+        }
+        return;
+    }
+
+    // with no hostile worth shooting the towers patch up our own creeps
+    let damaged = room
+        .find(find::MY_CREEPS, None)
+        .into_iter()
+        .filter(|c| c.hits() < c.hits_max())
+        .min_by(|a, b| health(a).total_cmp(&health(b)));
+    if let Some(creep) = damaged {
+        debug!("towers in {} healing {}", room.name(), creep.name());
+        for tower in towers.iter() {
+            if let Err(e) = tower.heal(&creep) {
+                debug!("tower could not heal: {e:?}");
+                continue;
+            }
+            draw_tower_action(room, tower.pos(), creep.pos(), "green");
+        }
+        return;
+    }
+
+    if !can_spare_energy(room) {
+        return;
+    }
+    for tower in towers.iter() {
+        let energy = tower.store().get_used_capacity(Some(ResourceType::Energy));
+        if energy <= TOWER_REPAIR_RESERVE {
+            continue;
+        }
+        let damaged = match with_repair_index(room, |index| index.take_best(tower.pos())) {
+            Some(d) => d,
+            None => return,
+        };
+        let structure = match damaged.id.resolve() {
+            Some(s) => StructureObject::from(s),
+            None => continue,
+        };
+        if let Some(repairable) = structure.as_repairable() {
+            debug!(
+                "tower in {} repairing {:?} at {}",
+                room.name(),
+                damaged.structure_type,
+                damaged.pos
+            );
+            if let Err(e) = tower.repair(repairable) {
+                debug!("tower could not repair: {e:?}");
+                continue;
+            }
+            draw_tower_action(room, tower.pos(), damaged.pos, "yellow");
+        }
+    }
+}
+
+fn health(creep: &Creep) -> f64 {
+    creep.hits() as f64 / creep.hits_max().max(1) as f64
+}
+
+fn can_spare_energy(room: &Room) -> bool {
+    match room.storage() {
+        Some(s) => s.store().get_used_capacity(Some(ResourceType::Energy)) > STORAGE_REPAIR_RESERVE,
+        None => room.energy_available() == room.energy_capacity_available(),
+    }
+}
+
+// damage a tower does at the given range
+pub fn tower_damage(range: u32) -> u32 {
+    let optimal = TOWER_OPTIMAL_RANGE as u32;
+    let falloff = TOWER_FALLOFF_RANGE as u32;
+    let range = range.clamp(optimal, falloff);
+    let ratio = 1.0 - TOWER_FALLOFF * (range - optimal) as f64 / (falloff - optimal) as f64;
+    (TOWER_POWER_ATTACK as f64 * ratio) as u32
+}
+
+// rough measure of how much harm a creep can do to us per tick, dismantlers and claimers included
+fn danger(creep: &Creep) -> u32 {
+    creep.get_active_bodyparts(Part::Attack) as u32 * ATTACK_POWER
+        + creep.get_active_bodyparts(Part::RangedAttack) as u32 * RANGED_ATTACK_POWER
+        + creep.get_active_bodyparts(Part::Work) as u32 * DISMANTLE_POWER
+        + creep.get_active_bodyparts(Part::Claim) as u32 * CLAIM_DANGER
+        + creep.get_active_bodyparts(Part::Heal) as u32 * HEAL_POWER
+}
+
+// healing the creep can get from itself and its friends this tick
+fn healing(creep: &Creep, hostiles: &[Creep]) -> u32 {
+    hostiles
+        .iter()
+        .map(|h| {
+            let heal = h.get_active_bodyparts(Part::Heal) as u32;
+            match h.pos().get_range_to(creep.pos()) {
+                0..=1 => heal * HEAL_POWER,
+                2..=3 => heal * RANGED_HEAL_POWER,
+                _ => 0,
+            }
+        })
+        .sum()
+}
+
+// the hostile the towers should focus: the most dangerous one the towers can actually out damage
+// its healers on. hostiles that heal faster than we hurt them are not worth the energy.
+fn pick_hostile(towers: &[StructureTower], hostiles: &[Creep]) -> Option<Creep> {
+    hostiles
+        .iter()
+        .filter_map(|h| {
+            let damage: u32 = towers
+                .iter()
+                .map(|t| tower_damage(t.pos().get_range_to(h.pos())))
+                .sum();
+            let net = damage.saturating_sub(healing(h, hostiles));
+            match net {
+                0 => {
+                    debug!("{} out heals the towers, not shooting", h.name());
+                    None
+                }
+                n => Some((danger(h), n, h)),
+            }
+        })
+        .max_by_key(|(danger, net, _)| (*danger, *net))
+        .map(|(_, _, h)| h.clone())
+}
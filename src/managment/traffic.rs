@@ -0,0 +1,212 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+use log::{debug, trace};
+use screeps::{
+    game, Creep, Direction, ErrorCode, HasPosition, Position, SharedCreepProperties, Terrain,
+};
+
+use crate::managment::{costs::with_room_costs, creep::CreepExtend};
+use crate::structs::{
+    tick::PerTick,
+    traffic::{MoveRequest, MoveState},
+};
+
+thread_local! {
+    // the moves creeps asked for this tick, keyed by creep name
+    static REQUESTS: RefCell<PerTick<HashMap<String, MoveRequest>>> =
+        RefCell::new(PerTick::default());
+}
+
+// asks to step in `direction` this tick, the step happens once every creep had its turn and
+// `resolve` decided who goes where. a tired creep just waits for its fatigue to go away
+pub fn submit(creep: &Creep, direction: Direction) -> Result<(), ErrorCode> {
+    if creep.fatigue() > 0 {
+        return Ok(());
+    }
+    let from = creep.pos();
+    let to = match from.checked_add_direction(direction) {
+        Ok(p) => p,
+        Err(_) => return Err(ErrorCode::InvalidArgs),
+    };
+    let priority = match creep.get_type() {
+        Ok(Some(t)) => t.move_priority(),
+        _ => 0,
+    };
+    REQUESTS.with(|requests| {
+        requests
+            .borrow_mut()
+            .current(game::time())
+            .insert(creep.name(), MoveRequest { from, to, priority });
+    });
+    Ok(())
+}
+
+// moves every creep that asked for it. creeps stepping onto each other's tiles swap, idle creeps
+// standing in the way get shoved aside or swapped back by creeps with a higher priority and the
+// creep with the higher priority wins a tile two creeps want
+pub fn resolve() {
+    let requests =
+        REQUESTS.with(|requests| requests.borrow_mut().take(game::time()).unwrap_or_default());
+    if requests.is_empty() {
+        return;
+    }
+    let creeps: HashMap<String, Creep> = game::creeps().entries().collect();
+    let occupants = creeps
+        .iter()
+        .map(|(name, creep)| (creep.pos(), name.clone()))
+        .collect();
+    let mut resolver = Resolver {
+        creeps: &creeps,
+        requests: &requests,
+        occupants,
+        claimed: HashSet::new(),
+        states: HashMap::new(),
+    };
+
+    let mut order: Vec<(&String, &MoveRequest)> = requests.iter().collect();
+    order.sort_by_key(|(_, r)| std::cmp::Reverse(r.priority));
+    for (name, _) in order {
+        resolver.resolve(name);
+    }
+}
+
+struct Resolver<'a> {
+    creeps: &'a HashMap<String, Creep>,
+    requests: &'a HashMap<String, MoveRequest>,
+    // who stands where, creeps that moved away are taken out
+    occupants: HashMap<Position, String>,
+    // tiles a creep moves onto this tick
+    claimed: HashSet<Position>,
+    states: HashMap<String, MoveState>,
+}
+
+impl Resolver<'_> {
+    // whether the creep gets to take its step
+    fn resolve(&mut self, name: &str) -> bool {
+        match self.states.get(name) {
+            Some(MoveState::Moved) => return true,
+            Some(MoveState::Stayed) | Some(MoveState::Resolving) => return false,
+            None => {}
+        }
+        let request = match self.requests.get(name) {
+            Some(r) => *r,
+            None => return false,
+        };
+        self.states.insert(name.to_string(), MoveState::Resolving);
+        if self.claimed.contains(&request.to) {
+            trace!("{name} waits, {} is taken", request.to);
+            return self.stay(name);
+        }
+        let occupant = match self.occupants.get(&request.to) {
+            Some(o) if o != name => o.clone(),
+            _ => return self.step(name, request.from, request.to),
+        };
+        match self.requests.get(&occupant) {
+            // both want each other's tile
+            Some(other) if other.to == request.from && !self.states.contains_key(&occupant) => {
+                match self.step(&occupant, other.from, other.to) {
+                    true => self.step(name, request.from, request.to),
+                    false => self.stay(name),
+                }
+            }
+            // the occupant is on its way somewhere else, follow once it made room
+            Some(_) => match self.resolve(&occupant) {
+                true => self.step(name, request.from, request.to),
+                false => self.stay(name),
+            },
+            None if self.states.contains_key(&occupant) => self.stay(name),
+            None => {
+                let creep = self.creeps.get(&occupant);
+                let priority = match creep.map(|c| c.get_type()) {
+                    Some(Ok(Some(t))) => t.move_priority(),
+                    _ => 0,
+                };
+                if priority >= request.priority {
+                    return self.stay(name);
+                }
+                // creeps working on their target stay where they are
+                let target = creep
+                    .and_then(|c| c.get_target().ok().flatten())
+                    .and_then(|t| Some((t.pos()?, t.range())));
+                if target.is_some_and(|(pos, range)| request.to.in_range_to(pos, range)) {
+                    trace!("{name} waits, {occupant} is working");
+                    return self.stay(name);
+                }
+                // out of the way if there is room next to it, otherwise onto our tile
+                let aside = self.free_tile(request.to, request.from, target);
+                debug!(
+                    "{name} moves {occupant} to {}",
+                    aside.unwrap_or(request.from)
+                );
+                match self.step(&occupant, request.to, aside.unwrap_or(request.from)) {
+                    true => self.step(name, request.from, request.to),
+                    false => self.stay(name),
+                }
+            }
+        }
+    }
+
+    fn step(&mut self, name: &str, from: Position, to: Position) -> bool {
+        let creep = match self.creeps.get(name) {
+            Some(c) => c,
+            None => return self.stay(name),
+        };
+        let direction = match from.get_direction_to(to) {
+            Some(d) => d,
+            None => return self.stay(name),
+        };
+        if let Err(e) = creep.move_direction(direction) {
+            debug!("{name} could not move: {e:?}");
+            return self.stay(name);
+        }
+        if self.occupants.get(&from).is_some_and(|o| o == name) {
+            self.occupants.remove(&from);
+        }
+        self.claimed.insert(to);
+        self.states.insert(name.to_string(), MoveState::Moved);
+        true
+    }
+
+    fn stay(&mut self, name: &str) -> bool {
+        self.states.insert(name.to_string(), MoveState::Stayed);
+        false
+    }
+
+    // a walkable tile next to `pos` nobody stands on or moves onto, not the one the shoving
+    // creep comes from and not on the room's edge where the creep would leave the room. tiles
+    // in range of the shoved creep's target come first
+    fn free_tile(
+        &self,
+        pos: Position,
+        from: Position,
+        target: Option<(Position, u32)>,
+    ) -> Option<Position> {
+        let room = game::rooms().get(pos.room_name())?;
+        let terrain = room.get_terrain();
+        let blocked = with_room_costs(&room, |costs| costs.static_layer.clone());
+        directions()
+            .filter_map(|d| pos.checked_add_direction(d).ok())
+            .filter(|p| *p != from && p.room_name() == pos.room_name() && !p.is_room_edge())
+            .filter(|p| terrain.get(p.x().u8(), p.y().u8()) != Terrain::Wall)
+            .filter(|p| blocked.get(p.xy()) < 255)
+            .filter(|p| !self.occupants.contains_key(p) && !self.claimed.contains(p))
+            .min_by_key(|p| target.is_some_and(|(t, range)| !p.in_range_to(t, range)))
+    }
+}
+
+fn directions() -> impl Iterator<Item = Direction> {
+    [
+        Direction::Top,
+        Direction::TopRight,
+        Direction::Right,
+        Direction::BottomRight,
+        Direction::Bottom,
+        Direction::BottomLeft,
+        Direction::Left,
+        Direction::TopLeft,
+    ]
+    .into_iter()
+}
@@ -0,0 +1,148 @@
+use log::{debug, error};
+use screeps::{
+    ConstructionSite, Creep, HasId, HasPosition, MaybeHasId, ObjectId, Part, Room,
+    SharedCreepProperties, StructureType,
+};
+
+use crate::{
+    managment::{census::RoomState, reservation::with_ledger},
+    roles::{repairer::repair_target, Role},
+    structs::{body::BodyTemplate, room::RoomExtend, target::CreepTarget},
+    CreepExtend,
+};
+
+// the order builders work through construction sites in when the room memory does not set one
+pub const DEFAULT_BUILD_PRIORITY: [StructureType; 9] = [
+    StructureType::Spawn,
+    StructureType::Extension,
+    StructureType::Tower,
+    StructureType::Container,
+    StructureType::Storage,
+    StructureType::Link,
+    StructureType::Road,
+    StructureType::Rampart,
+    StructureType::Wall,
+];
+
+pub struct Builder;
+
+impl Role for Builder {
+    fn run(&self, creep: Creep) {
+        run(creep)
+    }
+    fn body_template(&self) -> BodyTemplate {
+        BodyTemplate::repeat(vec![Part::Work, Part::Carry, Part::Move], 16)
+    }
+    // a builder for every 5 sites, capped so they do not starve the upgraders
+    fn population(&self, state: &RoomState) -> u32 {
+        match state.construction_sites {
+            0 => 0,
+            n => n.div_ceil(5).min(3),
+        }
+    }
+    fn spawn_priority(&self) -> u32 {
+        20
+    }
+}
+
+pub fn run(creep: Creep) {
+    let room = match creep.room() {
+        Some(r) => r,
+        None => return,
+    };
+    let target = match creep.get_target() {
+        Ok(t) => t,
+        Err(e) => {
+            error!("could not read target of {}: {e}", creep.name());
+            return;
+        }
+    };
+    let new_target = if creep.is_empty() {
+        match target {
+            Some(CreepTarget::Harvest(_)) => return,
+            _ => room
+                .get_best_source(&creep.get_blacklist())
+                .map(|s| CreepTarget::Harvest(s.id())),
+        }
+    } else if creep.is_full() || !matches!(target, Some(CreepTarget::Harvest(_))) {
+        match target {
+            Some(CreepTarget::Build(id)) if id.resolve().is_some() => return,
+            _ => match pick_site(&creep, &room) {
+                Some(id) => Some(CreepTarget::Build(id)),
+                // nothing to build, repair what is damaged or put the energy in the controller
+                None => match target {
+                    Some(CreepTarget::Repair(id)) if id.resolve().is_some() => return,
+                    Some(CreepTarget::Upgrade(_)) => return,
+                    _ => match repair_target(&creep, &room) {
+                        Some(t) => Some(t),
+                        None => room
+                            .controller()
+                            .filter(|c| !creep.avoids(c.id().into()))
+                            .map(|c| CreepTarget::Upgrade(c.id())),
+                    },
+                },
+            },
+        }
+    } else {
+        return;
+    };
+    if let Some(t) = new_target {
+        if let Err(err) = creep.set_target(Some(t)) {
+            error!("error setting creep_target: {err}")
+        }
+    }
+}
+
+// picks the construction site the builder should work on. sites are ranked by the room's build
+// priority, every creep that reserved a site pushes it one rank down so builders spread out.
+// ties go to the site closest to done, then the closest one. sites the creep abandoned are left
+// out
+pub fn pick_site(creep: &Creep, room: &Room) -> Option<ObjectId<ConstructionSite>> {
+    let priority = build_priority(room);
+    let sites = room.clone().get_construction_sites();
+    let blacklist = creep.get_blacklist();
+    with_ledger(|ledger| {
+        sites
+            .into_iter()
+            .filter_map(|site| site.try_id().map(|id| (id, site)))
+            .filter(|(id, _)| !blacklist.contains(&(*id).into()))
+            .min_by_key(|(id, site)| {
+                let rank = priority
+                    .iter()
+                    .position(|t| *t == site.structure_type())
+                    .unwrap_or(priority.len());
+                let builders = ledger.get((*id).into()).creeps as usize;
+                let remaining = site.progress_total().saturating_sub(site.progress());
+                (
+                    rank + builders,
+                    remaining,
+                    creep.pos().get_range_to(site.pos()),
+                )
+            })
+            .map(|(id, _)| {
+                debug!("{} picked construction site {id}", creep.name());
+                id
+            })
+    })
+}
+
+fn build_priority(room: &Room) -> Vec<StructureType> {
+    match room.clone().get_memory_obj() {
+        Ok(m) if !m.build_priority.is_empty() => m.build_priority,
+        _ => DEFAULT_BUILD_PRIORITY.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_builder_for_every_five_sites() {
+        let mut state = RoomState::default();
+        for (sites, builders) in [(0, 0), (1, 1), (5, 1), (6, 2), (40, 3)] {
+            state.construction_sites = sites;
+            assert_eq!(Builder.population(&state), builders, "{sites} sites");
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::str::FromStr;
+
+use log::{debug, error, info};
+use screeps::{
+    Creep, ErrorCode, HasPosition, OwnedStructureProperties, Part, Position, Room, RoomCoordinate,
+    RoomName, SharedCreepProperties,
+};
+
+use crate::{
+    managment::{
+        census::{claim_target, RoomState},
+        route::route,
+    },
+    roles::Role,
+    structs::{body::BodyTemplate, creep::CreepMemory, movement::MoveOptions},
+    CreepExtend,
+};
+
+pub struct Claimer;
+
+impl Role for Claimer {
+    fn run(&self, creep: Creep) {
+        run(creep)
+    }
+    fn body_template(&self) -> BodyTemplate {
+        BodyTemplate::repeat(vec![Part::Claim, Part::Move], 5)
+    }
+    // one claimer while the room has a room to claim that is not ours yet
+    fn population(&self, state: &RoomState) -> u32 {
+        match state.claim {
+            true => 1,
+            false => 0,
+        }
+    }
+    fn spawn_priority(&self) -> u32 {
+        5
+    }
+    fn uses_targets(&self) -> bool {
+        false
+    }
+    // the claimer is sent to the room its home room wants claimed
+    fn on_spawn(&self, room: &Room, memory: &mut CreepMemory) {
+        memory.target_room = claim_target(room).map(|r| r.to_string());
+    }
+}
+
+// walks to the room the claimer was sent to along the route there and claims its controller,
+// when we are out of gcl it reserves it instead
+pub fn run(creep: Creep) {
+    let target = match creep
+        .get_memory_obj()
+        .map(|m| m.target_room.and_then(|r| RoomName::from_str(&r).ok()))
+    {
+        Ok(Some(t)) => t,
+        Ok(None) => {
+            debug!("{} has no room to claim", creep.name());
+            return;
+        }
+        Err(e) => {
+            error!("could not read memory of {}: {e}", creep.name());
+            return;
+        }
+    };
+    let here = creep.pos().room_name();
+    if here != target {
+        if route(here, target).is_none() {
+            debug!("{} has no route to {target}", creep.name());
+            return;
+        }
+        // anywhere well inside the room will do, the controller is only visible from there
+        let center = match RoomCoordinate::new(25) {
+            Ok(c) => Position::new(c, c, target),
+            Err(_) => return,
+        };
+        if let Err(e) = creep.b_move(center, MoveOptions::default().range(20)) {
+            debug!("{} could not move to {target}: {e:?}", creep.name());
+        }
+        return;
+    }
+    let controller = match creep.room().and_then(|r| r.controller()) {
+        Some(c) => c,
+        None => {
+            debug!("{} has no controller to claim", creep.name());
+            return;
+        }
+    };
+    if controller.my() {
+        debug!("{} is in a room we already own", creep.name());
+        return;
+    }
+    let res = match creep.claim_controller(&controller) {
+        Err(ErrorCode::GclNotEnough) => creep.reserve_controller(&controller),
+        r => r,
+    };
+    match res {
+        Ok(()) => info!("{} working on controller in {target}", creep.name()),
+        Err(ErrorCode::NotInRange) => {
+            if let Err(e) = creep.b_move(controller.pos(), MoveOptions::default()) {
+                debug!("{} could not move to the controller: {e:?}", creep.name());
+            }
+        }
+        Err(e) => debug!("{} could not claim controller: {e:?}", creep.name()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_claimer_while_there_is_a_room_to_claim() {
+        let state = RoomState {
+            rcl: 4,
+            ..Default::default()
+        };
+        assert_eq!(Claimer.population(&state), 0);
+        let claiming = RoomState {
+            claim: true,
+            ..state
+        };
+        assert_eq!(Claimer.population(&claiming), 1);
+    }
+}
@@ -0,0 +1,80 @@
+use log::debug;
+use screeps::{find, Creep, ErrorCode, HasPosition, Part, SharedCreepProperties};
+
+use crate::{managment::census::RoomState, roles::Role, structs::body::BodyTemplate, CreepExtend};
+
+pub struct Defender;
+
+impl Role for Defender {
+    fn run(&self, creep: Creep) {
+        run(creep)
+    }
+    fn body_template(&self) -> BodyTemplate {
+        BodyTemplate::new(
+            vec![Part::Tough, Part::Attack, Part::Move, Part::Move],
+            vec![Part::Attack, Part::Move],
+            10,
+        )
+    }
+    // a defender for every hostile that can fight, a few at most, the towers do the rest
+    fn population(&self, state: &RoomState) -> u32 {
+        state.hostiles.min(3)
+    }
+    fn spawn_priority(&self) -> u32 {
+        35
+    }
+    // defenders go where the hostiles are
+    fn danger_tolerance(&self) -> u8 {
+        u8::MAX
+    }
+    fn uses_targets(&self) -> bool {
+        false
+    }
+}
+
+// goes after the closest hostile in the room with whatever attack parts it has
+pub fn run(creep: Creep) {
+    let hostile = match creep.pos().find_closest_by_range(find::HOSTILE_CREEPS) {
+        Some(h) => h,
+        None => {
+            debug!("{} has nothing to fight", creep.name());
+            return;
+        }
+    };
+    let (res, range) = match creep.get_active_bodyparts(Part::RangedAttack) {
+        0 => (creep.attack(&hostile), 1),
+        _ => (creep.ranged_attack(&hostile), 3),
+    };
+    match res {
+        Ok(()) => {}
+        Err(ErrorCode::NotInRange) => {
+            let _ = creep.b_move_range(hostile.pos(), range);
+        }
+        Err(e) => debug!("{} could not attack: {e:?}", creep.name()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_defender_per_hostile() {
+        let quiet = RoomState {
+            sources: 2,
+            rcl: 8,
+            ..Default::default()
+        };
+        assert_eq!(Defender.population(&quiet), 0);
+        let raided = RoomState {
+            hostiles: 2,
+            ..quiet.clone()
+        };
+        assert_eq!(Defender.population(&raided), 2);
+        let sieged = RoomState {
+            hostiles: 12,
+            ..quiet
+        };
+        assert_eq!(Defender.population(&sieged), 3);
+    }
+}
@@ -0,0 +1,260 @@
+use log::{debug, error, info};
+use screeps::{
+    find, game, look, ConstructionSite, Creep, HasHits, HasId, HasPosition, ObjectId, Part,
+    Position, ResourceType, Room, SharedCreepProperties, Source, StructureContainer,
+    StructureObject, StructureType, Terrain,
+};
+
+use crate::{
+    managment::{census::RoomState, intents},
+    roles::Role,
+    structs::{
+        body::BodyTemplate,
+        creep::{CreepMemory, CreepType},
+        intent::IntentKind,
+        room::RoomExtend,
+        source::SourceExtend,
+        target::CreepTarget,
+    },
+    CreepExtend,
+};
+
+pub struct Harvester;
+
+impl Role for Harvester {
+    fn run(&self, creep: Creep) {
+        run(creep)
+    }
+    // 5 work parts empty a source right before it regenerates
+    fn body_template(&self) -> BodyTemplate {
+        BodyTemplate::new(
+            vec![Part::Work, Part::Carry, Part::Move],
+            vec![Part::Work, Part::Work, Part::Move],
+            2,
+        )
+    }
+    // one miner per source
+    fn population(&self, state: &RoomState) -> u32 {
+        state.sources.max(1)
+    }
+    fn spawn_priority(&self) -> u32 {
+        30
+    }
+    fn gathers_energy(&self) -> bool {
+        true
+    }
+    fn uses_targets(&self) -> bool {
+        false
+    }
+    fn on_death(&self, name: &str, memory: &CreepMemory) {
+        if let (Some(source), None) = (memory.source, memory.replaced) {
+            info!("harvester {name} died without a successor, source {source} is free");
+        }
+    }
+}
+
+// a static miner: it claims a source, parks on the container tile next to it and harvests every
+// tick. the energy goes into the container or on the floor for others to pick up.
+pub fn run(creep: Creep) {
+    let room = match creep.room() {
+        Some(r) => r,
+        None => return,
+    };
+    // without anyone else to refill the spawn the harvester has to do it itself
+    let recovering = match room.clone().get_memory_obj() {
+        Ok(m) => m.recovery.is_some(),
+        Err(_) => false,
+    };
+    if recovering {
+        fill(&creep, &room);
+        return;
+    }
+    let mut memory = match creep.get_memory_obj() {
+        Ok(o) => o,
+        Err(e) => {
+            error!("could not read memory of {}: {e}", creep.name());
+            return;
+        }
+    };
+    // the miner issues its own intents, a left over target from recovery would fight it
+    if memory.target.is_some() {
+        memory.target = None;
+        if let Err(e) = creep.set_memory_obj(memory.clone()) {
+            error!("could not save memory of {}: {e}", creep.name());
+        }
+    }
+    let source = match memory.source.and_then(|id| id.resolve()) {
+        Some(s) => s,
+        None => match claim_source(&creep, &room) {
+            Some((source, slot)) => {
+                info!("{} claimed source {}", creep.name(), source.id());
+                memory.source = Some(source.id());
+                memory.slot = Some(slot);
+                if let Err(e) = creep.set_memory_obj(memory.clone()) {
+                    error!("could not save memory of {}: {e}", creep.name());
+                }
+                source
+            }
+            None => {
+                debug!("{} has no free source to mine", creep.name());
+                return;
+            }
+        },
+    };
+    let slot = match memory.slot {
+        Some(s) => s,
+        None => source.pos(),
+    };
+
+    if creep.pos() != slot {
+        if let Err(e) = creep.b_move_range(slot, 0) {
+            debug!("{} could not move to its slot: {e:?}", creep.name());
+        }
+        return;
+    }
+
+    let container = find_container(&room, &source);
+    if creep.get_energy().is_some() {
+        if let Some(c) = &container {
+            if c.hits() < c.hits_max() {
+                if let Some(Err(e)) = intents::issue(&creep, IntentKind::Repair, || creep.repair(c))
+                {
+                    debug!("{} could not repair its container: {e:?}", creep.name());
+                }
+                return;
+            }
+        } else if let Some(site) = find_container_site(&room, &source) {
+            if let Some(Err(e)) = intents::issue(&creep, IntentKind::Build, || creep.build(&site)) {
+                debug!("{} could not build its container: {e:?}", creep.name());
+            }
+            return;
+        } else {
+            let res = room.create_construction_site(
+                slot.x().0,
+                slot.y().0,
+                StructureType::Container,
+                None,
+            );
+            if let Err(e) = res {
+                debug!("could not place container for {}: {e:?}", source.id());
+            }
+        }
+    }
+    if let Some(Err(e)) = intents::issue(&creep, IntentKind::Harvest, || creep.harvest(&source)) {
+        debug!("{} could not harvest: {e:?}", creep.name());
+    }
+}
+
+// the refill behaviour used while the room is recovering, harvest and bring it to the spawn
+fn fill(creep: &Creep, room: &Room) {
+    let target = if creep.is_full() {
+        let spawn = room
+            .clone()
+            .get_spawn()
+            .into_iter()
+            .find(|s| s.store().get_free_capacity(Some(ResourceType::Energy)) > 0);
+        let controller = room.controller().filter(|c| !creep.avoids(c.id().into()));
+        match (spawn, controller) {
+            (Some(s), _) => Some(CreepTarget::Spawn(s.id())),
+            (None, Some(c)) => Some(CreepTarget::Upgrade(c.id())),
+            (None, None) => None,
+        }
+    } else if creep.is_empty() {
+        room.get_best_source(&creep.get_blacklist())
+            .map(|s| CreepTarget::Harvest(s.id()))
+    } else {
+        return;
+    };
+    if let Some(t) = target {
+        if let Err(err) = creep.set_target(Some(t)) {
+            error!("error setting creep_target: {err}")
+        }
+    }
+}
+
+// picks a source no other harvester is mining and the tile to mine it from, the container tile
+// when there is one
+fn claim_source(creep: &Creep, room: &Room) -> Option<(Source, Position)> {
+    let claimed = claimed_sources(creep);
+    let source = room
+        .clone()
+        .get_sources()
+        .into_iter()
+        .filter(|s| !claimed.contains(&s.id()))
+        .min_by_key(|s| s.pos().get_range_to(creep.pos()))?;
+    let slot = match find_container(room, &source) {
+        Some(c) => c.pos(),
+        None => match find_container_site(room, &source) {
+            Some(site) => site.pos(),
+            None => mining_slot(room, &source)?,
+        },
+    };
+    Some((source, slot))
+}
+
+// sources that already have a harvester, creeps that are about to be replaced hand their
+// source over to their successor so they do not count
+fn claimed_sources(creep: &Creep) -> Vec<ObjectId<Source>> {
+    game::creeps()
+        .values()
+        .filter(|c| c.name() != creep.name())
+        .filter_map(|c| c.get_memory_obj().ok())
+        .filter(|m| m._type == Some(CreepType::Harvester) && !m.replaced.unwrap_or(false))
+        .filter_map(|m| m.source)
+        .collect()
+}
+
+// the walkable tile next to the source that is closest to the spawn
+fn mining_slot(room: &Room, source: &Source) -> Option<Position> {
+    let terrain = room.get_terrain();
+    let spawn = room.clone().get_spawn().into_iter().next();
+    source
+        .clone()
+        .get_slots()
+        .into_iter()
+        .filter(|p| *p != source.pos())
+        .filter(|p| terrain.get(p.x().0, p.y().0) != Terrain::Wall)
+        .filter(|p| room.look_for_at_xy(look::STRUCTURES, p.x().0, p.y().0).is_empty())
+        .min_by_key(|p| match &spawn {
+            Some(s) => p.get_range_to(s.pos()),
+            None => 0,
+        })
+}
+
+pub fn find_container(room: &Room, source: &Source) -> Option<StructureContainer> {
+    room.find(find::STRUCTURES, None)
+        .into_iter()
+        .filter_map(|s| match s {
+            StructureObject::StructureContainer(c) => Some(c),
+            _ => None,
+        })
+        .find(|c| c.pos().is_near_to(source.pos()))
+}
+
+fn find_container_site(room: &Room, source: &Source) -> Option<ConstructionSite> {
+    room.clone()
+        .get_construction_sites()
+        .into_iter()
+        .filter(|s| s.structure_type() == StructureType::Container)
+        .find(|s| s.pos().is_near_to(source.pos()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_harvester_per_source() {
+        let mut state = RoomState::default();
+        for (sources, harvesters) in [(0, 1), (1, 1), (2, 2)] {
+            state.sources = sources;
+            assert_eq!(Harvester.population(&state), harvesters);
+        }
+    }
+
+    #[test]
+    fn enough_work_to_empty_a_source() {
+        let body = Harvester.body_template().build(12_900).unwrap();
+        assert_eq!(body.iter().filter(|p| **p == Part::Work).count(), 5);
+    }
+}
@@ -0,0 +1,222 @@
+use log::{debug, error};
+use screeps::{
+    Creep, ErrorCode, HasPosition, HasStore, MaybeHasId, ObjectId, Part, RawObjectId, Resource,
+    ResourceType, Ruin, SharedCreepProperties, StructureContainer, StructureExtension,
+    StructureLink, StructureSpawn, StructureStorage, StructureTower, Tombstone, Transferable,
+    Withdrawable,
+};
+
+use wasm_bindgen::JsCast;
+
+use crate::{
+    managment::{census::RoomState, intents, logistics::with_board},
+    roles::Role,
+    structs::{
+        body::BodyTemplate,
+        intent::IntentKind,
+        logistics::{HaulTask, Offer, OfferKind, Request, RequestKind},
+        movement::MoveOptions,
+    },
+    CreepExtend,
+};
+
+pub struct Hauler;
+
+impl Role for Hauler {
+    fn run(&self, creep: Creep) {
+        run(creep)
+    }
+    fn body_template(&self) -> BodyTemplate {
+        BodyTemplate::repeat(vec![Part::Carry, Part::Carry, Part::Move], 16)
+    }
+    // a hauler to carry away what every source's harvester mines
+    fn population(&self, state: &RoomState) -> u32 {
+        state.sources.max(1)
+    }
+    fn spawn_priority(&self) -> u32 {
+        25
+    }
+    fn gathers_energy(&self) -> bool {
+        true
+    }
+    fn uses_targets(&self) -> bool {
+        false
+    }
+}
+
+// haulers take a task from the room's logistics board, pick up from the offer and deliver it
+// to the request, then take the next task
+pub fn run(creep: Creep) {
+    let room = match creep.room() {
+        Some(r) => r,
+        None => return,
+    };
+    let mut memory = match creep.get_memory_obj() {
+        Ok(o) => o,
+        Err(e) => {
+            error!("could not read memory of {}: {e}", creep.name());
+            return;
+        }
+    };
+    // haulers issue their own intents
+    memory.target = None;
+
+    let task = match memory.haul.take() {
+        Some(t) => Some(t),
+        None => {
+            let carried = creep.store().store_types().into_iter().next();
+            let task = with_board(&room, |board| {
+                let task = match carried {
+                    Some(resource) => board.best_delivery(
+                        creep.pos(),
+                        resource,
+                        creep.store().get_used_capacity(Some(resource)),
+                    ),
+                    None => board.best_task(creep.pos(), creep.store().get_capacity(None)),
+                };
+                if let Some(t) = &task {
+                    board.reserve(t);
+                }
+                task
+            });
+            if task.is_none() {
+                debug!("{} has nothing to haul", creep.name());
+            }
+            task
+        }
+    };
+    memory.haul = task.and_then(|t| step(&creep, t));
+    if let Err(e) = creep.set_memory_obj(memory) {
+        error!("could not save memory of {}: {e}", creep.name());
+    }
+}
+
+// works on the task for this tick and returns what is left of it
+fn step(creep: &Creep, mut task: HaulTask) -> Option<HaulTask> {
+    if let Some(offer) = task.offer.clone() {
+        if creep.store().get_free_capacity(None) <= 0 {
+            task.offer = None;
+        } else if !creep.pos().is_near_to(offer.pos) {
+            if let Err(e) = creep.b_move(offer.pos, MoveOptions::default()) {
+                debug!("{} could not move to offer: {e:?}", creep.name());
+            }
+            return Some(task);
+        } else {
+            match take(creep, &offer, task.amount) {
+                Ok(()) => task.offer = None,
+                // another intent won this tick, try again next tick
+                Err(ErrorCode::Busy) => return Some(task),
+                // whatever was there is gone, deliver what we have
+                Err(ErrorCode::NotEnough) | Err(ErrorCode::NotFound) | Err(ErrorCode::Full) => {
+                    task.offer = None;
+                    if creep.is_empty() {
+                        return None;
+                    }
+                }
+                Err(e) => {
+                    error!("{} could not take from offer: {e:?}", creep.name());
+                    return None;
+                }
+            }
+            // the store only shows what we took next tick, deliver then
+            return Some(task);
+        }
+    }
+
+    if creep.store().get_used_capacity(Some(task.resource)) == 0 {
+        return None;
+    }
+    let request = task.request.clone();
+    if !creep.pos().is_near_to(request.pos) {
+        if let Err(e) = creep.b_move(request.pos, MoveOptions::default()) {
+            debug!("{} could not move to request: {e:?}", creep.name());
+        }
+        return Some(task);
+    }
+    match give(creep, &request, task.resource) {
+        Ok(()) | Err(ErrorCode::Full) | Err(ErrorCode::NotFound) => None,
+        Err(ErrorCode::Busy) => Some(task),
+        Err(e) => {
+            error!("{} could not deliver: {e:?}", creep.name());
+            None
+        }
+    }
+}
+
+// picks up or withdraws from the offer through the intent pipeline, Busy when an intent the
+// game prefers was already issued this tick
+fn take(creep: &Creep, offer: &Offer, amount: u32) -> Result<(), ErrorCode> {
+    match offer.kind {
+        OfferKind::Dropped => {
+            let resource = resolve::<Resource>(offer.id)?;
+            intents::issue(creep, IntentKind::Pickup, || creep.pickup(&resource))
+                .unwrap_or(Err(ErrorCode::Busy))
+        }
+        OfferKind::Container => withdraw::<StructureContainer>(creep, offer, amount),
+        OfferKind::Tombstone => withdraw::<Tombstone>(creep, offer, amount),
+        OfferKind::Ruin => withdraw::<Ruin>(creep, offer, amount),
+        OfferKind::Link => withdraw::<StructureLink>(creep, offer, amount),
+        OfferKind::Storage => withdraw::<StructureStorage>(creep, offer, amount),
+    }
+}
+
+fn give(creep: &Creep, request: &Request, resource: ResourceType) -> Result<(), ErrorCode> {
+    match request.kind {
+        RequestKind::Spawn => transfer::<StructureSpawn>(creep, request, resource),
+        RequestKind::Extension => transfer::<StructureExtension>(creep, request, resource),
+        RequestKind::Tower => transfer::<StructureTower>(creep, request, resource),
+        RequestKind::ControllerContainer => {
+            transfer::<StructureContainer>(creep, request, resource)
+        }
+        RequestKind::Storage => transfer::<StructureStorage>(creep, request, resource),
+    }
+}
+
+fn resolve<T>(id: RawObjectId) -> Result<T, ErrorCode>
+where
+    T: MaybeHasId + JsCast,
+{
+    ObjectId::<T>::from(id).resolve().ok_or(ErrorCode::NotFound)
+}
+
+fn withdraw<T>(creep: &Creep, offer: &Offer, amount: u32) -> Result<(), ErrorCode>
+where
+    T: Withdrawable + HasStore + MaybeHasId + JsCast,
+{
+    let target = resolve::<T>(offer.id)?;
+    let available = target.store().get_used_capacity(Some(offer.resource));
+    let free = creep.store().get_free_capacity(None).max(0) as u32;
+    let amount = amount.min(available).min(free);
+    if amount == 0 {
+        return Err(ErrorCode::NotEnough);
+    }
+    intents::issue(creep, IntentKind::Withdraw, || {
+        creep.withdraw(&target, offer.resource, Some(amount))
+    })
+    .unwrap_or(Err(ErrorCode::Busy))
+}
+
+fn transfer<T>(creep: &Creep, request: &Request, resource: ResourceType) -> Result<(), ErrorCode>
+where
+    T: Transferable + MaybeHasId + JsCast,
+{
+    let target = resolve::<T>(request.id)?;
+    intents::issue(creep, IntentKind::Transfer, || {
+        creep.transfer(&target, resource, None)
+    })
+    .unwrap_or(Err(ErrorCode::Busy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_hauler_per_source() {
+        let mut state = RoomState::default();
+        for (sources, haulers) in [(0, 1), (1, 1), (2, 2)] {
+            state.sources = sources;
+            assert_eq!(Hauler.population(&state), haulers);
+        }
+    }
+}
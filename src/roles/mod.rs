@@ -1 +1,140 @@
+use log::debug;
+use screeps::{Creep, Room};
+
+use crate::managment::census::RoomState;
+use crate::structs::{
+    body::BodyTemplate,
+    creep::{CreepMemory, CreepType},
+};
+
+pub mod builder;
+pub mod claimer;
+pub mod defender;
+pub mod harvester;
+pub mod hauler;
+pub mod repairer;
 pub mod unknown;
+pub mod upgrader;
+
+// everything the rest of the bot needs to know about a creep type. adding a role means adding
+// a file with an implementation of this and a line in `role`
+pub trait Role {
+    // decides what the creep does this tick, usually by setting its CreepTarget
+    fn run(&self, creep: Creep);
+    fn body_template(&self) -> BodyTemplate;
+    // how many of this role a room with this state should have
+    fn population(&self, state: &RoomState) -> u32;
+    // higher gets spawned first
+    fn spawn_priority(&self) -> u32;
+    // when two creeps want the same tile the higher one gets it and it may shove idle creeps
+    // with a lower one out of its way
+    fn move_priority(&self) -> u32 {
+        self.spawn_priority()
+    }
+    // how much danger the creep walks through without going around it, u8::MAX walks through
+    // anything
+    fn danger_tolerance(&self) -> u8 {
+        0
+    }
+    // creeps of this role bring energy into the room, without them the room can not spawn
+    fn gathers_energy(&self) -> bool {
+        false
+    }
+    // creeps of this role work through a CreepTarget instead of issuing their own intents
+    fn uses_targets(&self) -> bool {
+        true
+    }
+    // called right before the creep is spawned, the role can fill in the memory it starts with
+    fn on_spawn(&self, _room: &Room, _memory: &mut CreepMemory) {}
+    // called once the creep is gone with the memory it had left
+    fn on_death(&self, name: &str, _memory: &CreepMemory) {
+        debug!("{name} died");
+    }
+}
+
+// the role implementation of every creep type
+pub fn role(creep_type: &CreepType) -> &'static dyn Role {
+    match creep_type {
+        CreepType::Upgrader => &upgrader::Upgrader,
+        CreepType::Builder => &builder::Builder,
+        CreepType::Harvester => &harvester::Harvester,
+        CreepType::Hauler => &hauler::Hauler,
+        CreepType::Repairer => &repairer::Repairer,
+        CreepType::Claimer => &claimer::Claimer,
+        CreepType::Defender => &defender::Defender,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::body::body_cost;
+    use screeps::constants::{Part, MAX_CREEP_SIZE};
+
+    // every type with the energy its smallest body costs and the parts of its largest body
+    const BODIES: [(CreepType, u32, usize); 7] = [
+        (CreepType::Upgrader, 200, 48),
+        (CreepType::Builder, 200, 48),
+        (CreepType::Harvester, 200, 9),
+        (CreepType::Hauler, 150, 48),
+        (CreepType::Repairer, 200, 24),
+        (CreepType::Claimer, 650, 10),
+        (CreepType::Defender, 190, 24),
+    ];
+
+    #[test]
+    fn bodies_fit_every_budget() {
+        assert_eq!(BODIES.len(), CreepType::all().len());
+        for creep_type in CreepType::all() {
+            let (_, min_cost, max_parts) = BODIES
+                .iter()
+                .find(|(t, _, _)| *t == creep_type)
+                .cloned()
+                .unwrap();
+            let template = role(&creep_type).body_template();
+            assert_eq!(template.min_cost(), min_cost, "{creep_type}");
+            assert_eq!(template.build(min_cost - 1), None, "{creep_type}");
+            let smallest = template.build(min_cost).unwrap();
+            assert_eq!(body_cost(&smallest), min_cost, "{creep_type}");
+            assert!(smallest.contains(&Part::Move), "{creep_type}");
+            let largest = template.build(12_900).unwrap();
+            assert_eq!(largest.len(), max_parts, "{creep_type}");
+            assert!(largest.len() as u32 <= MAX_CREEP_SIZE, "{creep_type}");
+            // what a room at rcl 1 has is enough for every type a new room needs
+            if creep_type != CreepType::Claimer {
+                assert!(template.build(300).is_some(), "{creep_type}");
+            }
+        }
+    }
+
+    #[test]
+    fn energy_roles_spawn_before_the_ones_spending_it() {
+        let priority = |t: CreepType| role(&t).spawn_priority();
+        assert!(priority(CreepType::Harvester) > priority(CreepType::Hauler));
+        for spender in [
+            CreepType::Builder,
+            CreepType::Repairer,
+            CreepType::Upgrader,
+            CreepType::Claimer,
+        ] {
+            assert!(priority(CreepType::Hauler) > priority(spender));
+        }
+        // hostiles in the room come before the economy
+        assert!(priority(CreepType::Defender) > priority(CreepType::Harvester));
+    }
+
+    #[test]
+    fn empty_room_wants_its_economy() {
+        let state = RoomState {
+            sources: 2,
+            rcl: 1,
+            ..Default::default()
+        };
+        let population = |t: CreepType| role(&t).population(&state);
+        assert_eq!(population(CreepType::Harvester), 2);
+        assert_eq!(population(CreepType::Hauler), 2);
+        assert_eq!(population(CreepType::Upgrader), 2);
+        assert_eq!(population(CreepType::Builder), 0);
+        assert_eq!(population(CreepType::Repairer), 0);
+    }
+}
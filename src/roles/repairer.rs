@@ -0,0 +1,108 @@
+use log::{debug, error};
+use screeps::{Creep, HasId, HasPosition, Part, Room, SharedCreepProperties};
+
+use crate::{
+    managment::{census::RoomState, repair::with_repair_index},
+    roles::{builder::pick_site, Role},
+    structs::{body::BodyTemplate, room::RoomExtend, target::CreepTarget},
+    CreepExtend,
+};
+
+pub struct Repairer;
+
+impl Role for Repairer {
+    fn run(&self, creep: Creep) {
+        run(creep)
+    }
+    fn body_template(&self) -> BodyTemplate {
+        BodyTemplate::repeat(vec![Part::Work, Part::Carry, Part::Move], 8)
+    }
+    // until there are towers to do it a repairer keeps roads and containers alive
+    fn population(&self, state: &RoomState) -> u32 {
+        match state.damaged_structures {
+            0 => 0,
+            _ if state.rcl < 2 => 0,
+            _ => 1,
+        }
+    }
+    fn spawn_priority(&self) -> u32 {
+        15
+    }
+}
+
+// repairers keep the room's structures alive, when nothing is damaged they build and when
+// there is nothing to build either they upgrade
+pub fn run(creep: Creep) {
+    let room = match creep.room() {
+        Some(r) => r,
+        None => return,
+    };
+    let target = match creep.get_target() {
+        Ok(t) => t,
+        Err(e) => {
+            error!("could not read target of {}: {e}", creep.name());
+            return;
+        }
+    };
+    let new_target = if creep.is_empty() {
+        match target {
+            Some(CreepTarget::Harvest(_)) => return,
+            _ => room
+                .get_best_source(&creep.get_blacklist())
+                .map(|s| CreepTarget::Harvest(s.id())),
+        }
+    } else if creep.is_full() || !matches!(target, Some(CreepTarget::Harvest(_))) {
+        match target {
+            Some(CreepTarget::Repair(id)) if id.resolve().is_some() => return,
+            _ => match repair_target(&creep, &room) {
+                Some(t) => Some(t),
+                None => match pick_site(&creep, &room) {
+                    Some(id) => Some(CreepTarget::Build(id)),
+                    None => match target {
+                        Some(CreepTarget::Build(_)) | Some(CreepTarget::Upgrade(_)) => return,
+                        _ => room
+                            .controller()
+                            .filter(|c| !creep.avoids(c.id().into()))
+                            .map(|c| CreepTarget::Upgrade(c.id())),
+                    },
+                },
+            },
+        }
+    } else {
+        return;
+    };
+    if let Some(t) = new_target {
+        if let Err(err) = creep.set_target(Some(t)) {
+            error!("error setting creep_target: {err}")
+        }
+    }
+}
+
+// takes the most damaged structure near the creep out of the room's repair index
+pub fn repair_target(creep: &Creep, room: &Room) -> Option<CreepTarget> {
+    let damaged = with_repair_index(room, |index| index.take_best(creep.pos()))?;
+    debug!(
+        "{} repairs {:?} at {} ({}/{})",
+        creep.name(),
+        damaged.structure_type,
+        damaged.pos,
+        damaged.hits,
+        damaged.target
+    );
+    Some(CreepTarget::Repair(damaged.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_repairer_while_something_is_damaged() {
+        let mut state = RoomState::default();
+        for (rcl, damaged, repairers) in [(3, 0, 0), (1, 10, 0), (2, 1, 1), (6, 50, 1)] {
+            state.rcl = rcl;
+            state.damaged_structures = damaged;
+            assert_eq!(Repairer.population(&state), repairers, "{state:?}");
+        }
+    }
+}
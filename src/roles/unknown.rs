@@ -1,11 +1,187 @@
-use log::error;
-use screeps::Creep;
+use std::collections::HashMap;
 
-use crate::{structs::creep::CreepType, CreepExtend};
+use log::{error, info};
+use screeps::{Creep, Part, Room, SharedCreepProperties};
 
+use crate::{
+    structs::{creep::CreepType, room::RoomExtend},
+    CreepExtend,
+};
+
+// creeps without a type (spawned by hand or by an older version of the code) get the type that
+// fits their body best, where the body could do several jobs the home room's needs decide
 pub fn run(creep: Creep) {
-    let res = creep.set_type(Some(CreepType::Upgrader));
-    if let Err(err) = res {
+    let room = match creep.get_home_room() {
+        Ok(Some(r)) => Some(r),
+        _ => creep.room(),
+    };
+    let deficit = match &room {
+        Some(r) => population_deficit(r),
+        None => HashMap::new(),
+    };
+    let parts: Vec<Part> = creep.body().iter().map(|p| p.part()).collect();
+    let (creep_type, reason) = infer_role(&parts, &deficit);
+    info!("{} is now a {creep_type}: {reason}", creep.name());
+
+    let mut memory = match creep.get_memory_obj() {
+        Ok(o) => o,
+        Err(e) => {
+            error!("could not read memory of {}: {e}", creep.name());
+            return;
+        }
+    };
+    if memory.homeroom.is_none() {
+        memory = memory.set_homeroom(room);
+    }
+    memory._type = Some(creep_type);
+    memory.role_reason = Some(reason);
+    if let Err(err) = creep.set_memory_obj(memory) {
         error!("error setting creep type {err}")
     }
 }
+
+// how many creeps of every type the room is short of
+fn population_deficit(room: &Room) -> HashMap<CreepType, i64> {
+    let population = match room.clone().get_memory_obj() {
+        Ok(m) => m.population,
+        Err(_) => HashMap::new(),
+    };
+    population
+        .into_iter()
+        .map(|(t, target)| {
+            let alive = t.amount_alive(Some(room.clone())).unwrap_or_default();
+            (t, target as i64 - alive as i64)
+        })
+        .collect()
+}
+
+// picks the type for a body and says why
+pub fn infer_role(parts: &[Part], deficit: &HashMap<CreepType, i64>) -> (CreepType, String) {
+    let count = |part: Part| parts.iter().filter(|p| **p == part).count();
+    let (work, carry) = (count(Part::Work), count(Part::Carry));
+    let fighting = count(Part::Attack) + count(Part::RangedAttack);
+
+    if count(Part::Claim) > 0 {
+        return (
+            CreepType::Claimer,
+            format!("has {} claim parts", count(Part::Claim)),
+        );
+    }
+    if fighting > 0 || (count(Part::Heal) > 0 && work == 0) {
+        return (
+            CreepType::Defender,
+            format!("has {fighting} attack and {} heal parts", count(Part::Heal)),
+        );
+    }
+    match (work, carry) {
+        (0, 0) => (
+            CreepType::Upgrader,
+            "has no work or carry parts, nothing fits".to_string(),
+        ),
+        (0, c) => (CreepType::Hauler, format!("only carries ({c} carry parts)")),
+        (w, 0) => (
+            CreepType::Harvester,
+            format!("works without carrying ({w} work parts)"),
+        ),
+        (w, c) => {
+            // a work heavy body mines well, everything else with work and carry is a worker
+            let mut candidates = vec![CreepType::Builder, CreepType::Upgrader, CreepType::Repairer];
+            if w > c {
+                candidates.push(CreepType::Harvester);
+            }
+            let short = candidates
+                .into_iter()
+                .map(|t| (deficit.get(&t).copied().unwrap_or(0), t))
+                .filter(|(d, _)| *d > 0)
+                .max_by_key(|(d, t)| (*d, t.spawn_priority()));
+            match short {
+                Some((d, t)) => (
+                    t.clone(),
+                    format!("{w} work and {c} carry parts and the home room is short {d} {t}"),
+                ),
+                None => (
+                    CreepType::Upgrader,
+                    format!(
+                        "{w} work and {c} carry parts and the home room is not short of workers"
+                    ),
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deficit(short: &[(CreepType, i64)]) -> HashMap<CreepType, i64> {
+        short.iter().cloned().collect()
+    }
+
+    fn role_of(parts: &[Part], short: &[(CreepType, i64)]) -> CreepType {
+        infer_role(parts, &deficit(short)).0
+    }
+
+    #[test]
+    fn claim_parts_make_a_claimer() {
+        let parts = [Part::Claim, Part::Work, Part::Carry, Part::Move];
+        assert_eq!(role_of(&parts, &[]), CreepType::Claimer);
+    }
+
+    #[test]
+    fn attack_parts_make_a_defender() {
+        let parts = [Part::Attack, Part::Work, Part::Carry, Part::Move];
+        assert_eq!(role_of(&parts, &[]), CreepType::Defender);
+        assert_eq!(
+            role_of(&[Part::RangedAttack, Part::Move], &[]),
+            CreepType::Defender
+        );
+        assert_eq!(role_of(&[Part::Heal, Part::Move], &[]), CreepType::Defender);
+    }
+
+    #[test]
+    fn carry_and_move_make_a_hauler() {
+        let parts = [Part::Carry, Part::Carry, Part::Move];
+        let short = [(CreepType::Builder, 3)];
+        assert_eq!(role_of(&parts, &short), CreepType::Hauler);
+    }
+
+    #[test]
+    fn work_without_carry_makes_a_harvester() {
+        let parts = [Part::Work, Part::Work, Part::Move];
+        assert_eq!(role_of(&parts, &[]), CreepType::Harvester);
+    }
+
+    #[test]
+    fn work_heavy_bodies_fill_the_biggest_deficit() {
+        let parts = [Part::Work, Part::Work, Part::Work, Part::Carry, Part::Move];
+        let short = [(CreepType::Harvester, 1), (CreepType::Builder, 2)];
+        assert_eq!(role_of(&parts, &short), CreepType::Builder);
+        let short = [(CreepType::Harvester, 1), (CreepType::Upgrader, -2)];
+        assert_eq!(role_of(&parts, &short), CreepType::Harvester);
+        // with no work heavy body harvesting is not an option
+        let parts = [Part::Work, Part::Carry, Part::Move];
+        let short = [(CreepType::Harvester, 3), (CreepType::Repairer, 1)];
+        assert_eq!(role_of(&parts, &short), CreepType::Repairer);
+    }
+
+    #[test]
+    fn deficit_ties_go_to_the_higher_spawn_priority() {
+        let parts = [Part::Work, Part::Work, Part::Carry, Part::Move];
+        let short = [
+            (CreepType::Upgrader, 1),
+            (CreepType::Builder, 1),
+            (CreepType::Harvester, 1),
+        ];
+        assert_eq!(role_of(&parts, &short), CreepType::Harvester);
+        let parts = [Part::Work, Part::Carry, Part::Move];
+        assert_eq!(role_of(&parts, &short), CreepType::Builder);
+    }
+
+    #[test]
+    fn workers_without_a_deficit_upgrade() {
+        let parts = [Part::Work, Part::Carry, Part::Move];
+        let short = [(CreepType::Builder, 0), (CreepType::Repairer, -1)];
+        assert_eq!(role_of(&parts, &short), CreepType::Upgrader);
+    }
+}
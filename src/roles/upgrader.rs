@@ -0,0 +1,109 @@
+use log::{debug, error};
+use screeps::{Creep, HasId, Part, ResourceType};
+
+use crate::{
+    managment::census::RoomState,
+    roles::Role,
+    structs::{body::BodyTemplate, room::RoomExtend, target::CreepTarget},
+    CreepExtend,
+};
+
+pub struct Upgrader;
+
+impl Role for Upgrader {
+    fn run(&self, creep: Creep) {
+        run(creep)
+    }
+    fn body_template(&self) -> BodyTemplate {
+        BodyTemplate::repeat(vec![Part::Work, Part::Carry, Part::Move], 16)
+    }
+    // one upgrader per source, more when the storage is overflowing. at rcl 8 the controller
+    // only takes 15 energy per tick so one is enough
+    fn population(&self, state: &RoomState) -> u32 {
+        if state.rcl >= 8 {
+            return 1;
+        }
+        let mut upgraders = state.sources.max(1);
+        if let Some(energy) = state.storage_energy {
+            upgraders += (energy / 50_000).min(3);
+        }
+        upgraders
+    }
+    fn spawn_priority(&self) -> u32 {
+        10
+    }
+}
+
+pub fn run(creep: Creep) {
+    let room = match creep.room() {
+        Some(r) => r,
+        None => return,
+    };
+    let target = if creep.is_full() {
+        room.controller()
+            .filter(|c| !creep.avoids(c.id().into()))
+            .map(|c| CreepTarget::Upgrade(c.id()))
+    } else if creep.is_empty() && matches!(creep.get_target(), Ok(None)) {
+        // with energy in storage fetch it and go straight to the controller
+        if let (Some(storage), Some(controller)) = (room.storage(), room.controller()) {
+            let stored = storage
+                .store()
+                .get_used_capacity(Some(ResourceType::Energy));
+            if stored >= creep.store().get_capacity(None) {
+                let tasks = vec![
+                    CreepTarget::Withdraw(storage.id().into(), ResourceType::Energy),
+                    CreepTarget::Upgrade(controller.id()),
+                ];
+                if let Err(err) = creep.set_tasks(tasks) {
+                    error!("error setting creep tasks: {err}")
+                }
+                return;
+            }
+        }
+        room.get_best_source(&creep.get_blacklist())
+            .map(|s| CreepTarget::Harvest(s.id()))
+    } else {
+        debug!("nothing needs to happen");
+        return;
+    };
+    if let Some(t) = target {
+        if let Err(err) = creep.set_target(Some(t)) {
+            error!("error setting creep_target: {err}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_upgrader_per_source_and_more_with_storage() {
+        let mut state = RoomState {
+            rcl: 4,
+            ..Default::default()
+        };
+        for (sources, storage, upgraders) in [
+            (0, None, 1),
+            (2, None, 2),
+            (2, Some(49_999), 2),
+            (2, Some(120_000), 4),
+            (2, Some(900_000), 5),
+        ] {
+            state.sources = sources;
+            state.storage_energy = storage;
+            assert_eq!(Upgrader.population(&state), upgraders, "{state:?}");
+        }
+    }
+
+    #[test]
+    fn one_upgrader_at_rcl_8() {
+        let state = RoomState {
+            sources: 2,
+            rcl: 8,
+            storage_energy: Some(900_000),
+            ..Default::default()
+        };
+        assert_eq!(Upgrader.population(&state), 1);
+    }
+}
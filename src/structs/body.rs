@@ -0,0 +1,210 @@
+use screeps::constants::{Part, MAX_CREEP_SIZE};
+
+// describes how a body grows with the energy a room has. the base parts are always included
+// once, the repeat parts get added as many times as the energy (and max_repeat) allows.
+#[derive(Debug, Clone)]
+pub struct BodyTemplate {
+    pub base: Vec<Part>,
+    pub repeat: Vec<Part>,
+    pub max_repeat: u32,
+}
+
+#[allow(dead_code)]
+impl BodyTemplate {
+    pub fn new(base: Vec<Part>, repeat: Vec<Part>, max_repeat: u32) -> Self {
+        BodyTemplate {
+            base,
+            repeat,
+            max_repeat,
+        }
+    }
+    pub fn repeat(repeat: Vec<Part>, max_repeat: u32) -> Self {
+        Self::new(vec![], repeat, max_repeat)
+    }
+    // the cost of the smallest body this template can produce
+    pub fn min_cost(&self) -> u32 {
+        let base = body_cost(&self.base);
+        match self.base.is_empty() {
+            true => base + body_cost(&self.repeat),
+            false => base,
+        }
+    }
+    // builds the largest body that fits in the energy budget and the 50 part limit.
+    // returns None when not even the smallest version of the body is affordable.
+    pub fn build(&self, energy: u32) -> Option<Vec<Part>> {
+        if energy < self.min_cost() || self.base.len() as u32 > MAX_CREEP_SIZE {
+            return None;
+        }
+        let mut body = self.base.clone();
+        let mut cost = body_cost(&body);
+        let repeat_cost = body_cost(&self.repeat);
+        let mut repeats = 0;
+        while repeats < self.max_repeat
+            && !self.repeat.is_empty()
+            && cost + repeat_cost <= energy
+            && (body.len() + self.repeat.len()) as u32 <= MAX_CREEP_SIZE
+        {
+            body.extend(self.repeat.iter().copied());
+            cost += repeat_cost;
+            repeats += 1;
+        }
+        if body.is_empty() {
+            return None;
+        }
+        Some(sort_body(body))
+    }
+}
+
+pub fn body_cost(body: &[Part]) -> u32 {
+    body.iter().map(|p| p.cost()).sum()
+}
+
+// puts tough parts first so they soak damage, heal parts last so they survive longest and
+// spreads the move parts evenly through the rest of the body
+pub fn sort_body(body: Vec<Part>) -> Vec<Part> {
+    let tough: Vec<Part> = body.iter().copied().filter(|p| *p == Part::Tough).collect();
+    let moves = body.iter().filter(|p| **p == Part::Move).count();
+    let mut rest: Vec<Part> = body
+        .iter()
+        .copied()
+        .filter(|p| *p != Part::Tough && *p != Part::Move)
+        .collect();
+    rest.sort_by_key(|p| part_order(*p));
+
+    let mut sorted = tough;
+    let mut placed = 0;
+    for (i, part) in rest.iter().enumerate() {
+        sorted.push(*part);
+        let due = (i + 1) * moves / rest.len();
+        while placed < due {
+            sorted.push(Part::Move);
+            placed += 1;
+        }
+    }
+    while placed < moves {
+        sorted.push(Part::Move);
+        placed += 1;
+    }
+    sorted
+}
+
+fn part_order(part: Part) -> u8 {
+    match part {
+        Part::Tough => 0,
+        Part::Work => 1,
+        Part::Carry => 2,
+        Part::Claim => 3,
+        Part::Attack => 4,
+        Part::RangedAttack => 5,
+        Part::Heal => 6,
+        _ => 7,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(body: &[Part], part: Part) -> usize {
+        body.iter().filter(|p| **p == part).count()
+    }
+
+    #[test]
+    fn below_min_cost_builds_nothing() {
+        let template = BodyTemplate::new(vec![Part::Work, Part::Carry, Part::Move], vec![], 0);
+        assert_eq!(template.min_cost(), 200);
+        assert_eq!(template.build(199), None);
+
+        let template = BodyTemplate::repeat(vec![Part::Carry, Part::Move], 5);
+        assert_eq!(template.min_cost(), 100);
+        assert_eq!(template.build(99), None);
+    }
+
+    #[test]
+    fn exact_min_cost_builds_smallest_body() {
+        let template = BodyTemplate::new(
+            vec![Part::Work, Part::Carry, Part::Move],
+            vec![Part::Work, Part::Move],
+            5,
+        );
+        let body = template.build(template.min_cost()).unwrap();
+        assert_eq!(body.len(), 3);
+        assert_eq!(body_cost(&body), 200);
+
+        let template = BodyTemplate::repeat(vec![Part::Carry, Part::Move], 5);
+        let body = template.build(template.min_cost()).unwrap();
+        assert_eq!(body, vec![Part::Carry, Part::Move]);
+    }
+
+    #[test]
+    fn repeats_stop_at_max_repeat() {
+        let template = BodyTemplate::new(vec![Part::Move], vec![Part::Work], 3);
+        let body = template.build(10_000).unwrap();
+        assert_eq!(count(&body, Part::Work), 3);
+        assert_eq!(body.len(), 4);
+    }
+
+    #[test]
+    fn repeats_stop_at_part_limit() {
+        let template = BodyTemplate::repeat(vec![Part::Carry, Part::Carry, Part::Move], 100);
+        let body = template.build(100_000).unwrap();
+        // a 17th repeat would make 51 parts
+        assert_eq!(body.len(), 48);
+        assert!(body.len() as u32 <= MAX_CREEP_SIZE);
+
+        let template = BodyTemplate::new(vec![Part::Move; 51], vec![], 0);
+        assert_eq!(template.build(100_000), None);
+    }
+
+    #[test]
+    fn tough_parts_come_first() {
+        let body = sort_body(vec![
+            Part::Attack,
+            Part::Move,
+            Part::Tough,
+            Part::Heal,
+            Part::Tough,
+            Part::Move,
+        ]);
+        assert_eq!(&body[..2], &[Part::Tough, Part::Tough]);
+        assert_eq!(body.len(), 6);
+        assert_eq!(count(&body, Part::Move), 2);
+        // heal keeps behind the attack part
+        let attack = body.iter().position(|p| *p == Part::Attack);
+        let heal = body.iter().position(|p| *p == Part::Heal);
+        assert!(attack < heal);
+    }
+
+    #[test]
+    fn move_parts_are_spread_out() {
+        let mut body = vec![Part::Work; 4];
+        body.extend([Part::Move; 2]);
+        let body = sort_body(body);
+        assert_eq!(
+            body,
+            vec![
+                Part::Work,
+                Part::Work,
+                Part::Move,
+                Part::Work,
+                Part::Work,
+                Part::Move,
+            ]
+        );
+
+        let mut body = vec![Part::Move; 3];
+        body.extend([Part::Carry; 3]);
+        let body = sort_body(body);
+        assert_eq!(
+            body,
+            vec![
+                Part::Carry,
+                Part::Move,
+                Part::Carry,
+                Part::Move,
+                Part::Carry,
+                Part::Move,
+            ]
+        );
+    }
+}
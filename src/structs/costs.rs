@@ -0,0 +1,68 @@
+use screeps::{LocalCostMatrix, RoomXY};
+
+use super::tick::PerTick;
+
+// the parts of a room's cost matrix a search wants
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostLayers {
+    // roads, structures in the way and ramparts
+    pub structures: bool,
+    // where creeps stand this tick
+    pub creeps: bool,
+    // keeper lairs, hostile towers and hostile creeps, only the danger above the tolerance
+    // counts. None leaves the layer out
+    pub danger: Option<u8>,
+}
+
+#[allow(dead_code)]
+impl CostLayers {
+    pub const STATIC: CostLayers = CostLayers {
+        structures: true,
+        creeps: false,
+        danger: None,
+    };
+    pub const ALL: CostLayers = CostLayers {
+        structures: true,
+        creeps: true,
+        danger: None,
+    };
+    pub fn danger(mut self, tolerance: Option<u8>) -> Self {
+        self.danger = tolerance;
+        self
+    }
+}
+
+// the cost matrix layers of one room kept on the heap
+#[derive(Debug, Default, Clone)]
+pub struct RoomCosts {
+    // how many structures and construction sites the static layer was built from, a different
+    // count means something was built or destroyed
+    pub structures: Option<usize>,
+    // whether the structure count was compared this tick
+    pub checked: PerTick<()>,
+    pub static_layer: LocalCostMatrix,
+    // the tiles creeps stand on this tick
+    pub creeps: PerTick<Vec<RoomXY>>,
+}
+
+impl RoomCosts {
+    // the static layer with the creep layer on top, only the layers asked for. roads cost
+    // `road`, what a road is worth depends on the creep walking it
+    pub fn matrix(&self, layers: CostLayers, road: u8) -> LocalCostMatrix {
+        let mut matrix = match layers.structures {
+            true => self.static_layer.clone(),
+            false => LocalCostMatrix::new(),
+        };
+        if layers.structures && road != 1 {
+            for (_, cost) in matrix.iter_mut().filter(|(_, c)| **c == 1) {
+                *cost = road;
+            }
+        }
+        if layers.creeps {
+            for xy in self.creeps.last().into_iter().flatten() {
+                matrix.set(*xy, 255);
+            }
+        }
+        matrix
+    }
+}
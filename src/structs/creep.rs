@@ -1,16 +1,19 @@
 
-use screeps::Room
-;
+use screeps::{ObjectId, Position, RawObjectId, Room, Source};
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display};
 
-use super::{ target::CreepTarget};
+use super::{logistics::HaulTask, path::CachedPath, target::CreepTarget, task::TaskQueue};
 impl Display for CreepType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             CreepType::Builder => write!(f, "builder"),
             CreepType::Upgrader => write!(f, "upgrader"),
             CreepType::Harvester => write!(f, "harvester"),
+            CreepType::Hauler => write!(f, "hauler"),
+            CreepType::Repairer => write!(f, "repairer"),
+            CreepType::Claimer => write!(f, "claimer"),
+            CreepType::Defender => write!(f, "defender"),
         }
     }
 }
@@ -22,25 +25,126 @@ pub struct CreepMemory {
     #[serde(rename = "type")]
     pub _type: Option<CreepType>,
     pub target: Option<CreepTarget>,
+    // what the creep does once its target is done
+    #[serde(default)]
+    pub tasks: TaskQueue,
+    // set once a successor for this creep has been queued
+    pub replaced: Option<bool>,
+    // ticks it takes to walk from the spawn to where this creep works
+    pub travel_time: Option<u32>,
+    // the source a harvester mines and the tile it mines it from
+    pub source: Option<ObjectId<Source>>,
+    pub slot: Option<Position>,
+    // the logistics job a hauler is working on
+    pub haul: Option<HaulTask>,
+    // why a creep spawned without a type got the type it has
+    pub role_reason: Option<String>,
+    // the last few times the creep was moved to a different role
+    #[serde(default)]
+    pub reassignments: Vec<Reassignment>,
+    // targets the creep abandoned
+    #[serde(default)]
+    pub blacklist: Vec<Blacklisted>,
+    // the path the creep is walking
+    pub path: Option<CachedPath>,
+    // the room a claimer is sent to
+    pub target_room: Option<String>,
 }
 impl CreepMemory {
     pub fn set_homeroom(mut self, room: Option<Room>) -> Self {
-        self.homeroom = match room {
-            Some(s) => Some(s.name().to_string()),
-            None => None,
-        };
+        self.homeroom = room.map(|s| s.name().to_string());
         self
     }
     pub fn set_type(mut self, creep_type: Option<CreepType>) -> Self {
         self._type = creep_type;
         self
     }
+    pub fn is_blacklisted(&self, id: RawObjectId, time: u32) -> bool {
+        self.blacklist.iter().any(|b| b.id == id && b.until > time)
+    }
+    // the targets the creep still stays away from
+    pub fn blacklisted(&self, time: u32) -> Vec<RawObjectId> {
+        self.blacklist
+            .iter()
+            .filter(|b| b.until > time)
+            .map(|b| b.id)
+            .collect()
+    }
+    pub fn blacklist(&mut self, id: RawObjectId, time: u32, ticks: u32) {
+        self.blacklist.retain(|b| b.until > time && b.id != id);
+        self.blacklist.push(Blacklisted {
+            id,
+            until: time + ticks,
+        });
+    }
+    // the memory a replacement of this creep starts with, it takes over the job where the
+    // current creep left it
+    pub fn successor(&self) -> Self {
+        CreepMemory {
+            homeroom: self.homeroom.clone(),
+            _type: self._type.clone(),
+            target: self.target.clone(),
+            travel_time: self.travel_time,
+            source: self.source,
+            slot: self.slot,
+            target_room: self.target_room.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Reassignment {
+    pub time: u32,
+    pub from: CreepType,
+    pub to: CreepType,
+    pub reason: String,
 }
 
+// keeps track of how a creep is doing on its current target so it can give up on targets it
+// can not reach or make no progress on
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct CreepMemoryMove {
-    pub time: u128,
+    // tick the target was assigned
+    #[serde(default)]
+    pub time: u32,
     pub dest: Option<CreepMemoryMoveDest>,
+    // where the creep was and how much it carried last tick
+    #[serde(default)]
+    pub pos: Option<Position>,
+    #[serde(default)]
+    pub load: u32,
+    // ticks in a row the creep neither moved nor changed what it carries
+    #[serde(default)]
+    pub idle: u32,
+}
+impl CreepMemoryMove {
+    pub fn new(time: u32, dest: Option<Position>) -> Self {
+        CreepMemoryMove {
+            time,
+            dest: dest.map(|p| CreepMemoryMoveDest {
+                x: p.x().0 as u64,
+                y: p.y().0 as u64,
+                room: p.room_name().to_string(),
+            }),
+            ..Default::default()
+        }
+    }
+    // every action a target has moves the creep or changes its store, a creep where neither
+    // happens is not getting anywhere. a tick spent `waiting` on something that is not up to
+    // the creep, like a source regenerating, counts neither as idle nor as time on the target
+    pub fn observe(&mut self, pos: Position, load: u32, waiting: bool) {
+        match self.pos {
+            _ if waiting => {
+                self.time += 1;
+                self.idle = 0;
+            }
+            Some(p) if p == pos && self.load == load => self.idle += 1,
+            _ => self.idle = 0,
+        }
+        self.pos = Some(pos);
+        self.load = load;
+    }
 }
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 
@@ -49,7 +153,13 @@ pub struct CreepMemoryMoveDest {
     pub y: u64,
     pub room: String,
 }
-#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, PartialOrd)]
+// a target the creep gave up on and will not take again until the tick in `until`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Blacklisted {
+    pub id: RawObjectId,
+    pub until: u32,
+}
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq, Hash, PartialOrd)]
 pub enum CreepType {
     #[default]
     #[serde(rename = "upgrader")]
@@ -58,4 +168,12 @@ pub enum CreepType {
     Builder,
     #[serde(rename = "harvester")]
     Harvester,
+    #[serde(rename = "hauler")]
+    Hauler,
+    #[serde(rename = "repairer")]
+    Repairer,
+    #[serde(rename = "claimer")]
+    Claimer,
+    #[serde(rename = "defender")]
+    Defender,
 }
@@ -0,0 +1,53 @@
+use screeps::{Position, RoomName};
+use serde::{Deserialize, Serialize};
+
+// what we know about a room from the last time we saw it
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct RoomIntel {
+    // game tick the room was last visible
+    pub seen: u32,
+    // the player owning the controller, when it is not us
+    pub owner: Option<String>,
+    // the player reserving the controller, when it is not us
+    pub reserved_by: Option<String>,
+    pub keeper: bool,
+    pub highway: bool,
+    // source keeper lairs and towers of other players
+    #[serde(default)]
+    pub lairs: Vec<Position>,
+    #[serde(default)]
+    pub towers: Vec<Position>,
+    // where hostile creeps that can attack stood the last time there were any and when that was
+    #[serde(default)]
+    pub hostiles: Vec<Position>,
+    #[serde(default)]
+    pub hostiles_seen: u32,
+    // game tick the lairs, towers or hostiles last changed, paths searched before that did not
+    // know about the danger there is now
+    #[serde(default)]
+    pub changed: u32,
+}
+
+impl RoomIntel {
+    pub fn hostile(&self) -> bool {
+        self.owner.is_some()
+    }
+    // whether the danger layer built from `other` would look different
+    pub fn same_danger(&self, other: &RoomIntel) -> bool {
+        self.lairs == other.lairs && self.towers == other.towers && self.hostiles == other.hostiles
+    }
+}
+
+// highways and source keeper rooms follow from where the room is in its sector, a sector is
+// 10 by 10 rooms with highways on its border and keepers around its center
+pub fn room_kind(name: RoomName) -> (bool, bool) {
+    // W and N rooms count from -1 down
+    let sector = |c: i32| match c < 0 {
+        true => (-c - 1) % 10,
+        false => c % 10,
+    };
+    let (x, y) = (sector(name.x_coord()), sector(name.y_coord()));
+    let highway = x == 0 || y == 0;
+    let keeper = (4..=6).contains(&x) && (4..=6).contains(&y) && !(x == 5 && y == 5);
+    (highway, keeper)
+}
@@ -0,0 +1,156 @@
+// the creep actions that take part in the game's intent pipelines. actions in the same pipeline
+// can not happen in the same tick, when a creep issues several of them only the one with the
+// highest priority runs. everything else (moving, upgrading, picking up) runs alongside.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntentKind {
+    Harvest,
+    Attack,
+    Build,
+    Repair,
+    Dismantle,
+    AttackController,
+    RangedHeal,
+    Heal,
+    RangedAttack,
+    RangedMassAttack,
+    Withdraw,
+    Transfer,
+    Drop,
+    Upgrade,
+    Pickup,
+}
+
+// the pipelines from lowest to highest priority
+const MELEE: [IntentKind; 8] = [
+    IntentKind::Harvest,
+    IntentKind::Attack,
+    IntentKind::Build,
+    IntentKind::Repair,
+    IntentKind::Dismantle,
+    IntentKind::AttackController,
+    IntentKind::RangedHeal,
+    IntentKind::Heal,
+];
+const RANGED: [IntentKind; 5] = [
+    IntentKind::RangedAttack,
+    IntentKind::RangedMassAttack,
+    IntentKind::Build,
+    IntentKind::Repair,
+    IntentKind::RangedHeal,
+];
+const RESOURCES: [IntentKind; 3] = [IntentKind::Withdraw, IntentKind::Transfer, IntentKind::Drop];
+const PIPELINES: [&[IntentKind]; 3] = [&MELEE, &RANGED, &RESOURCES];
+
+// what happens when a creep that already issued `issued` this tick adds another intent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conflict {
+    // both run
+    None,
+    // the new intent would be thrown away by the game, do not bother issuing it
+    Blocked(IntentKind),
+    // the new intent wins and the game throws away the earlier one
+    Overrides(IntentKind),
+}
+
+impl IntentKind {
+    // the priority of the intent in every pipeline it takes part in
+    fn ranks(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        PIPELINES
+            .iter()
+            .enumerate()
+            .filter_map(move |(p, pipeline)| {
+                pipeline.iter().position(|k| k == self).map(|r| (p, r))
+            })
+    }
+
+    pub fn conflict(&self, issued: &[IntentKind]) -> Conflict {
+        // calling the same action twice keeps only the last call
+        if issued.contains(self) {
+            return Conflict::Blocked(*self);
+        }
+        let mut conflict = Conflict::None;
+        for (pipeline, rank) in self.ranks() {
+            for other in issued {
+                let other_rank = match other.ranks().find(|(p, _)| *p == pipeline) {
+                    Some((_, r)) => r,
+                    None => continue,
+                };
+                if other_rank >= rank {
+                    return Conflict::Blocked(*other);
+                }
+                conflict = Conflict::Overrides(*other);
+            }
+        }
+        conflict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use IntentKind::*;
+
+    const ALL: [IntentKind; 15] = [
+        Harvest,
+        Attack,
+        Build,
+        Repair,
+        Dismantle,
+        AttackController,
+        RangedHeal,
+        Heal,
+        RangedAttack,
+        RangedMassAttack,
+        Withdraw,
+        Transfer,
+        Drop,
+        Upgrade,
+        Pickup,
+    ];
+
+    // a row for every new intent with a column for every issued one in the order of `ALL`:
+    // `.` both run, `b` the new one is blocked, `o` the new one overrides the issued one
+    const TABLE: [&str; 15] = [
+        "bbbbbbbb.......", // harvest
+        "obbbbbbb.......", // attack
+        "oobbbbbboo.....", // build
+        "ooobbbbboo.....", // repair
+        "oooobbbb.......", // dismantle
+        "ooooobbb.......", // attack controller
+        "oooooobboo.....", // ranged heal
+        "ooooooob.......", // heal
+        "..bb..b.bb.....", // ranged attack
+        "..bb..b.ob.....", // ranged mass attack
+        "..........bbb..", // withdraw
+        "..........obb..", // transfer
+        "..........oob..", // drop
+        ".............b.", // upgrade
+        "..............b", // pickup
+    ];
+
+    #[test]
+    fn conflict_of_every_pair() {
+        for (new, row) in ALL.iter().zip(TABLE) {
+            for (issued, cell) in ALL.iter().zip(row.chars()) {
+                let expected = match cell {
+                    'b' => Conflict::Blocked(*issued),
+                    'o' => Conflict::Overrides(*issued),
+                    _ => Conflict::None,
+                };
+                assert_eq!(
+                    new.conflict(&[*issued]),
+                    expected,
+                    "{new:?} after {issued:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn the_highest_issued_intent_decides() {
+        // a build overrides the harvest but not the heal issued with it
+        assert_eq!(Build.conflict(&[Harvest, Heal]), Conflict::Blocked(Heal));
+        assert_eq!(Heal.conflict(&[Harvest, Build]), Conflict::Overrides(Build));
+        assert_eq!(Transfer.conflict(&[Harvest, Upgrade]), Conflict::None);
+    }
+}
@@ -0,0 +1,295 @@
+use screeps::{Position, RawObjectId, ResourceType};
+use serde::{Deserialize, Serialize};
+
+// everything that has resources lying around for a hauler to take
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OfferKind {
+    Container,
+    Dropped,
+    Tombstone,
+    Ruin,
+    Link,
+    Storage,
+}
+
+// everything that wants resources brought to it
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RequestKind {
+    Spawn,
+    Extension,
+    Tower,
+    ControllerContainer,
+    Storage,
+}
+
+impl RequestKind {
+    // requests with a higher priority are served first
+    pub fn priority(&self) -> u32 {
+        match self {
+            RequestKind::Spawn => 50,
+            RequestKind::Extension => 50,
+            RequestKind::Tower => 40,
+            RequestKind::ControllerContainer => 20,
+            RequestKind::Storage => 10,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Offer {
+    pub id: RawObjectId,
+    pub kind: OfferKind,
+    pub pos: Position,
+    pub resource: ResourceType,
+    pub amount: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Request {
+    pub id: RawObjectId,
+    pub kind: RequestKind,
+    pub pos: Position,
+    // None takes any resource, storage is happy with everything
+    pub resource: Option<ResourceType>,
+    pub amount: u32,
+}
+
+impl Request {
+    pub fn accepts(&self, resource: ResourceType) -> bool {
+        match self.resource {
+            Some(r) => r == resource,
+            None => true,
+        }
+    }
+}
+
+// a job for a single hauler: take `amount` of `resource` from the offer and bring it to the
+// request. the offer is None when the hauler already carries what it delivers.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HaulTask {
+    pub offer: Option<Offer>,
+    pub request: Request,
+    pub resource: ResourceType,
+    pub amount: u32,
+}
+
+// all offers and requests of a single room
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct LogisticsBoard {
+    pub offers: Vec<Offer>,
+    pub requests: Vec<Request>,
+}
+
+impl LogisticsBoard {
+    pub fn offer(&mut self, offer: Offer) {
+        if offer.amount > 0 {
+            self.offers.push(offer);
+        }
+    }
+    pub fn request(&mut self, request: Request) {
+        if request.amount > 0 {
+            self.requests.push(request);
+        }
+    }
+
+    // takes the amounts of a task off the board so no other hauler gets matched to them
+    pub fn reserve(&mut self, task: &HaulTask) {
+        if let Some(offer) = &task.offer {
+            if let Some(o) = self
+                .offers
+                .iter_mut()
+                .find(|o| o.id == offer.id && o.resource == task.resource)
+            {
+                o.amount = o.amount.saturating_sub(task.amount);
+            }
+        }
+        if let Some(r) = self.requests.iter_mut().find(|r| r.id == task.request.id) {
+            r.amount = r.amount.saturating_sub(task.amount);
+        }
+    }
+
+    // finds the best offer and request pair for an empty hauler at `pos` that can carry
+    // `capacity`. the most important requests win, between those the pair that moves the most
+    // resources per tile walked.
+    pub fn best_task(&self, pos: Position, capacity: u32) -> Option<HaulTask> {
+        let mut best: Option<((u32, u32), HaulTask)> = None;
+        for request in self.requests.iter().filter(|r| r.amount > 0) {
+            for offer in self.offers.iter().filter(|o| o.amount > 0) {
+                if offer.id == request.id || !request.accepts(offer.resource) {
+                    continue;
+                }
+                // no point in moving things from storage to storage
+                if offer.kind == OfferKind::Storage && request.kind == RequestKind::Storage {
+                    continue;
+                }
+                let amount = offer.amount.min(request.amount).min(capacity);
+                if amount == 0 {
+                    continue;
+                }
+                let trip = pos.get_range_to(offer.pos) + offer.pos.get_range_to(request.pos);
+                let score = (request.kind.priority(), amount * 100 / (trip + 1));
+                if best.as_ref().is_none_or(|(s, _)| score > *s) {
+                    best = Some((
+                        score,
+                        HaulTask {
+                            offer: Some(offer.clone()),
+                            request: request.clone(),
+                            resource: offer.resource,
+                            amount,
+                        },
+                    ));
+                }
+            }
+        }
+        best.map(|(_, task)| task)
+    }
+
+    // finds where a hauler that already carries `amount` of `resource` should bring it
+    pub fn best_delivery(
+        &self,
+        pos: Position,
+        resource: ResourceType,
+        amount: u32,
+    ) -> Option<HaulTask> {
+        self.requests
+            .iter()
+            .filter(|r| r.amount > 0 && r.accepts(resource))
+            .max_by_key(|r| {
+                (
+                    r.kind.priority(),
+                    std::cmp::Reverse(pos.get_range_to(r.pos)),
+                )
+            })
+            .map(|r| HaulTask {
+                offer: None,
+                request: r.clone(),
+                resource,
+                amount: amount.min(r.amount),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screeps::{RoomCoordinate, RoomName};
+    use std::str::FromStr;
+
+    fn id(n: u8) -> RawObjectId {
+        RawObjectId::from_str(&format!("5bbcab1d9099fc012e6342{n:02x}")).unwrap()
+    }
+
+    fn pos(x: u8, y: u8) -> Position {
+        Position::new(
+            RoomCoordinate::new(x).unwrap(),
+            RoomCoordinate::new(y).unwrap(),
+            RoomName::from_str("W1N1").unwrap(),
+        )
+    }
+
+    fn offer(n: u8, kind: OfferKind, at: Position, amount: u32) -> Offer {
+        Offer {
+            id: id(n),
+            kind,
+            pos: at,
+            resource: ResourceType::Energy,
+            amount,
+        }
+    }
+
+    fn request(n: u8, kind: RequestKind, at: Position, amount: u32) -> Request {
+        Request {
+            id: id(n),
+            kind,
+            pos: at,
+            resource: Some(ResourceType::Energy),
+            amount,
+        }
+    }
+
+    #[test]
+    fn important_requests_win_over_closer_ones() {
+        let mut board = LogisticsBoard::default();
+        board.offer(offer(1, OfferKind::Container, pos(10, 10), 500));
+        board.request(request(
+            2,
+            RequestKind::ControllerContainer,
+            pos(11, 10),
+            500,
+        ));
+        board.request(request(3, RequestKind::Extension, pos(40, 40), 50));
+        let task = board.best_task(pos(10, 11), 100).unwrap();
+        assert_eq!(task.request.id, id(3));
+        assert_eq!(task.amount, 50);
+    }
+
+    #[test]
+    fn most_resources_per_tile_wins() {
+        let mut board = LogisticsBoard::default();
+        board.request(request(1, RequestKind::Tower, pos(10, 10), 1000));
+        // the same amount closer by wins
+        board.offer(offer(2, OfferKind::Container, pos(20, 10), 100));
+        board.offer(offer(3, OfferKind::Container, pos(12, 10), 100));
+        let task = board.best_task(pos(10, 10), 100).unwrap();
+        assert_eq!(task.offer.map(|o| o.id), Some(id(3)));
+
+        // a full load a bit further away beats a few resources next door
+        let mut board = LogisticsBoard::default();
+        board.request(request(1, RequestKind::Tower, pos(10, 10), 1000));
+        board.offer(offer(2, OfferKind::Dropped, pos(11, 10), 20));
+        board.offer(offer(3, OfferKind::Container, pos(13, 10), 400));
+        let task = board.best_task(pos(10, 10), 100).unwrap();
+        assert_eq!(task.offer.map(|o| o.id), Some(id(3)));
+        assert_eq!(task.amount, 100);
+    }
+
+    #[test]
+    fn storage_is_not_moved_to_storage() {
+        let mut board = LogisticsBoard::default();
+        board.offer(offer(1, OfferKind::Storage, pos(10, 10), 10_000));
+        board.request(request(2, RequestKind::Storage, pos(12, 10), 10_000));
+        assert_eq!(board.best_task(pos(10, 10), 100), None);
+
+        board.request(request(3, RequestKind::Spawn, pos(20, 10), 300));
+        let task = board.best_task(pos(10, 10), 100).unwrap();
+        assert_eq!(task.request.id, id(3));
+    }
+
+    #[test]
+    fn reserved_amounts_are_not_matched_twice() {
+        let mut board = LogisticsBoard::default();
+        board.offer(offer(1, OfferKind::Container, pos(10, 10), 150));
+        board.request(request(2, RequestKind::Spawn, pos(20, 10), 300));
+
+        let first = board.best_task(pos(10, 10), 100).unwrap();
+        assert_eq!(first.amount, 100);
+        board.reserve(&first);
+        assert_eq!(board.offers[0].amount, 50);
+        assert_eq!(board.requests[0].amount, 200);
+
+        let second = board.best_task(pos(10, 10), 100).unwrap();
+        assert_eq!(second.amount, 50);
+        board.reserve(&second);
+        assert_eq!(board.best_task(pos(10, 10), 100), None);
+    }
+
+    #[test]
+    fn deliveries_go_to_the_nearest_important_request() {
+        let mut board = LogisticsBoard::default();
+        board.request(request(1, RequestKind::Storage, pos(11, 10), 10_000));
+        board.request(request(2, RequestKind::Extension, pos(30, 10), 50));
+        board.request(request(3, RequestKind::Extension, pos(15, 10), 50));
+        let task = board
+            .best_delivery(pos(10, 10), ResourceType::Energy, 100)
+            .unwrap();
+        assert_eq!(task.request.id, id(3));
+        assert_eq!(task.amount, 50);
+        assert_eq!(task.offer, None);
+
+        board.reserve(&task);
+        let task = board
+            .best_delivery(pos(10, 10), ResourceType::Energy, 100)
+            .unwrap();
+        assert_eq!(task.request.id, id(2));
+    }
+}
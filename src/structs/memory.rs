@@ -6,17 +6,21 @@ use log::error;
 use screeps::{
     game::{self, cpu},
     memory::ROOT,
-    HasId, Mineral, ObjectId, Room, Source, StructureController,
+    Mineral, ObjectId, Source, StructureController, StructureType,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Error;
 use wasm_bindgen::JsValue;
 
-use crate::structs::creep::CreepMemory;
+use crate::managment::path::path_counts;
+use crate::managment::stuck::stuck_counts;
+use crate::structs::creep::{CreepMemory, CreepType};
 
 use super::{
-    room::RoomExtend,
-    stats::{StatPerformance, Stats, StatsResources},
+    intel::RoomIntel,
+    spawn::SpawnQueue,
+    stats::{StatCreeps, StatPaths, StatPerformance, Stats, StatsResources},
+    target::TargetLimits,
 };
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -25,13 +29,35 @@ pub struct GlobalMemory {
     pub stats: Option<Stats>,
     pub rooms: Option<std::collections::HashMap<String, RoomMemory>>,
 }
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct RoomMemory {
+    #[serde(default)]
     pub sources: Vec<ObjectId<Source>>,
     pub controller: Option<ObjectId<StructureController>>,
     pub mineral: Option<MineralMemory>,
+    #[serde(default)]
+    pub spawn_queue: SpawnQueue,
+    // computed every tick by the census
+    #[serde(default)]
+    pub population: HashMap<CreepType, u32>,
+    // set from the console to force a population target, e.g.
+    // Memory.rooms.W1N1.population_override.upgrader = 4
+    #[serde(default)]
+    pub population_override: HashMap<CreepType, u32>,
+    // game tick the room went into recovery mode, None when the economy is running
+    pub recovery: Option<u32>,
+    // the order builders pick construction sites in, empty uses the default order
+    #[serde(default)]
+    pub build_priority: Vec<StructureType>,
+    // what we saw the last time the room was visible
+    pub intel: Option<RoomIntel>,
+    // how long creeps of this room work on a target before they give up on it
+    #[serde(default)]
+    pub target_limits: TargetLimits,
+    // a room the claimers of this room go and claim
+    pub claim: Option<String>,
 }
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 
 pub struct MineralMemory {
     pub id: Option<ObjectId<Mineral>>,
@@ -47,22 +73,9 @@ impl GlobalMemory {
     pub fn get_stats(&self) -> Option<Stats> {
         self.stats.clone()
     }
-    pub fn update_room(&self, room: Room) {
-        let sources: Vec<ObjectId<Source>> = room
-            .clone()
-            .get_sources()
-            .iter()
-            .map(|source| source.id())
-            .collect();
-
-        let controller = room.get_controller_id();
-        RoomMemory {
-            sources,
-            controller,
-            mineral: None,
-        };
-    }
     pub fn update_stats(&self) {
+        let stuck = stuck_counts();
+        let (paths, saved) = path_counts();
         let stats = Some(Stats {
             resrouces: Some(StatsResources {
                 pixel: None,
@@ -75,6 +88,16 @@ impl GlobalMemory {
                 limit: Some(game::cpu::limit()),
                 max: Some(game::cpu::tick_limit()),
             }),
+            creeps: Some(StatCreeps {
+                stuck: Some(stuck.stuck),
+                abandoned: Some(stuck.abandoned),
+            }),
+            paths: Some(StatPaths {
+                searches: Some(paths.searches),
+                cpu: Some(paths.cpu_searching),
+                reused: Some(paths.reused + paths.shared),
+                saved: Some(saved),
+            }),
         });
 
         let val = JsValue::from_serde(&stats);
@@ -1,8 +1,21 @@
+pub mod body;
+pub mod costs;
 pub mod creep;
+pub mod intel;
+pub mod intent;
+pub mod logistics;
 pub mod memory;
+pub mod movement;
+pub mod path;
+pub mod repair;
+pub mod reservation;
 pub mod room;
 pub mod source;
+pub mod spawn;
 pub mod stats;
 pub mod target;
+pub mod task;
+pub mod tick;
+pub mod traffic;
 pub mod visual;
 // these contain structs that are used by multiple modules. These should not have any logic in them that is done by the managment modules.
\ No newline at end of file
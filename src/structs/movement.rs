@@ -0,0 +1,197 @@
+use screeps::{constants::CARRY_CAPACITY, Part, Position};
+
+// how `b_move` gets a creep where it is going, built up like the pathfinder's SearchOptions:
+// MoveOptions::default().range(3).swamp_cost(20)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveOptions {
+    pub range: u32,
+    // positions to get away from, each until the creep is at least its range away
+    pub flee: Vec<(Position, u32)>,
+    pub max_ops: Option<u32>,
+    pub max_rooms: Option<u8>,
+    // terrain costs, the ones left out come from the creep's body
+    pub road_cost: Option<u8>,
+    pub plain_cost: Option<u8>,
+    pub swamp_cost: Option<u8>,
+    // creeps standing in the way get swapped or shoved by the traffic manager, set this to
+    // false to path around them instead
+    pub ignore_creeps: bool,
+    // how much danger the path may go through before it goes around, left out it comes from
+    // the creep's role. u8::MAX goes through anything
+    pub danger_tolerance: Option<u8>,
+}
+
+impl Default for MoveOptions {
+    fn default() -> Self {
+        MoveOptions {
+            range: 1,
+            flee: Vec::new(),
+            max_ops: None,
+            max_rooms: None,
+            road_cost: None,
+            plain_cost: None,
+            swamp_cost: None,
+            ignore_creeps: true,
+            danger_tolerance: None,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl MoveOptions {
+    pub fn range(mut self, range: u32) -> Self {
+        self.range = range;
+        self
+    }
+    pub fn flee(mut self, from: Position, range: u32) -> Self {
+        self.flee.push((from, range));
+        self
+    }
+    pub fn max_ops(mut self, ops: u32) -> Self {
+        self.max_ops = Some(ops);
+        self
+    }
+    pub fn max_rooms(mut self, rooms: u8) -> Self {
+        self.max_rooms = Some(rooms);
+        self
+    }
+    pub fn road_cost(mut self, cost: u8) -> Self {
+        self.road_cost = Some(cost);
+        self
+    }
+    pub fn plain_cost(mut self, cost: u8) -> Self {
+        self.plain_cost = Some(cost);
+        self
+    }
+    pub fn swamp_cost(mut self, cost: u8) -> Self {
+        self.swamp_cost = Some(cost);
+        self
+    }
+    pub fn ignore_creeps(mut self, ignore: bool) -> Self {
+        self.ignore_creeps = ignore;
+        self
+    }
+    pub fn danger_tolerance(mut self, tolerance: u8) -> Self {
+        self.danger_tolerance = Some(tolerance);
+        self
+    }
+    // the costs set here with the body's costs and the role's tolerance filling in the rest
+    pub fn costs(&self, body: TerrainCosts, tolerance: u8) -> PathCosts {
+        let tolerance = self.danger_tolerance.unwrap_or(tolerance);
+        PathCosts {
+            terrain: TerrainCosts {
+                road: self.road_cost.unwrap_or(body.road),
+                plain: self.plain_cost.unwrap_or(body.plain),
+                swamp: self.swamp_cost.unwrap_or(body.swamp),
+            },
+            danger: match tolerance {
+                u8::MAX => None,
+                t => Some(t),
+            },
+        }
+    }
+}
+
+// everything a path search needs to know about the creep it is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PathCosts {
+    pub terrain: TerrainCosts,
+    // the danger tolerance, None leaves the danger layer out
+    pub danger: Option<u8>,
+}
+
+// what stepping onto each kind of terrain costs a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TerrainCosts {
+    pub road: u8,
+    pub plain: u8,
+    pub swamp: u8,
+}
+
+impl Default for TerrainCosts {
+    // a creep with one move part for every other part
+    fn default() -> Self {
+        TerrainCosts {
+            road: 1,
+            plain: 2,
+            swamp: 10,
+        }
+    }
+}
+
+impl TerrainCosts {
+    // the ticks a body with `moves` move parts and `weight` parts that weigh something takes
+    // per tile. every weighing part makes 1 fatigue on roads, 2 on plains and 10 in swamps
+    // and every move part takes 2 away each tick
+    pub fn for_body(moves: u32, weight: u32) -> TerrainCosts {
+        if moves == 0 {
+            return TerrainCosts::default();
+        }
+        let ticks = |fatigue: u32| (fatigue * weight).div_ceil(moves * 2).clamp(1, 254) as u8;
+        TerrainCosts {
+            road: ticks(1),
+            plain: ticks(2),
+            swamp: ticks(10),
+        }
+    }
+
+    // the parts of a body that weigh something, move parts never do and carry parts only while
+    // they hold resources, `used` is what the creep carries
+    pub fn weight(body: &[Part], used: u32) -> u32 {
+        let count = |part: Part| body.iter().filter(|p| **p == part).count() as u32;
+        let carry = count(Part::Carry);
+        let loaded = used.div_ceil(CARRY_CAPACITY).min(carry);
+        body.len() as u32 - count(Part::Move) - carry + loaded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn costs(road: u8, plain: u8, swamp: u8) -> TerrainCosts {
+        TerrainCosts { road, plain, swamp }
+    }
+
+    #[test]
+    fn full_creeps_are_slower_than_empty_ones() {
+        use Part::*;
+        let body = [Work, Carry, Carry, Carry, Move, Move];
+        let moves = 2;
+        assert_eq!(TerrainCosts::weight(&body, 0), 1);
+        assert_eq!(TerrainCosts::weight(&body, 60), 3);
+        // a creep carrying more than its carry parts hold still only weighs its carry parts
+        assert_eq!(TerrainCosts::weight(&body, 1_000), 4);
+        assert_eq!(
+            TerrainCosts::for_body(moves, TerrainCosts::weight(&body, 0)),
+            costs(1, 1, 3)
+        );
+        assert_eq!(
+            TerrainCosts::for_body(moves, TerrainCosts::weight(&body, 150)),
+            costs(1, 2, 10)
+        );
+    }
+
+    #[test]
+    fn move_ratio() {
+        // one move part for every other part walks plains in a tick
+        assert_eq!(TerrainCosts::for_body(4, 4), costs(1, 1, 5));
+        // one for every two walks roads in a tick and plains in two
+        assert_eq!(TerrainCosts::for_body(2, 4), costs(1, 2, 10));
+        assert_eq!(TerrainCosts::for_body(2, 4), TerrainCosts::default());
+        // nothing to carry goes as fast as a tile can be walked
+        assert_eq!(TerrainCosts::for_body(2, 0), costs(1, 1, 1));
+    }
+
+    #[test]
+    fn no_move_parts() {
+        assert_eq!(TerrainCosts::for_body(0, 4), TerrainCosts::default());
+        assert_eq!(TerrainCosts::for_body(0, 0), TerrainCosts::default());
+    }
+
+    #[test]
+    fn costs_stay_in_range() {
+        assert_eq!(TerrainCosts::for_body(1, 49), costs(25, 49, 245));
+        assert_eq!(TerrainCosts::for_body(1, 100).swamp, 254);
+    }
+}
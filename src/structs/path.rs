@@ -0,0 +1,266 @@
+use screeps::{Direction, Position, RoomName};
+use serde::{Deserialize, Serialize};
+
+// a path kept in creep memory between ticks: where the creep stands before its next step and
+// the directions of the steps left, one digit each
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CachedPath {
+    pub dest: Position,
+    pub range: u32,
+    pub start: Position,
+    pub steps: String,
+    // ticks in a row the creep tried to take the next step and did not get anywhere
+    #[serde(default)]
+    pub blocked: u32,
+    // whether the creep was rested and asked to take the next step last tick
+    #[serde(default)]
+    pub stepped: bool,
+    // the tick the path was searched and the rooms it goes through, it is only as safe as the
+    // intel of those rooms was then
+    #[serde(default)]
+    pub time: u32,
+    #[serde(default)]
+    pub rooms: Vec<RoomName>,
+}
+
+impl CachedPath {
+    pub fn new(start: Position, dest: Position, range: u32, steps: String, time: u32) -> Self {
+        let mut path = CachedPath {
+            dest,
+            range,
+            start,
+            steps,
+            blocked: 0,
+            stepped: false,
+            time,
+            rooms: vec![],
+        };
+        for pos in path.positions() {
+            if path.rooms.last() != Some(&pos.room_name()) {
+                path.rooms.push(pos.room_name());
+            }
+        }
+        path
+    }
+    // every tile of the path from the start on
+    pub fn positions(&self) -> Vec<Position> {
+        let mut positions = vec![self.start];
+        let mut pos = self.start;
+        for d in self.steps.chars().filter_map(direction) {
+            pos = match pos.checked_add_direction(d) {
+                Ok(p) => p,
+                Err(_) => break,
+            };
+            positions.push(pos);
+        }
+        positions
+    }
+    pub fn next(&self) -> Option<Direction> {
+        self.steps.chars().next().and_then(direction)
+    }
+    // drops the first step once the creep took it
+    pub fn advance(&mut self) -> bool {
+        let next = match self
+            .next()
+            .and_then(|d| self.start.checked_add_direction(d).ok())
+        {
+            Some(p) => p,
+            None => return false,
+        };
+        self.start = next;
+        self.steps.remove(0);
+        self.blocked = 0;
+        true
+    }
+    // counts last tick's step as blocked when the rested creep asked for it and is still where it
+    // stepped from, then syncs the path to the creep
+    pub fn observe(&mut self, pos: Position) -> bool {
+        if self.stepped && self.start == pos {
+            self.blocked += 1;
+        }
+        self.stepped = false;
+        self.sync(pos)
+    }
+    // the same path for a creep at `pos`, starting at the tile of the path closest to the end
+    // that the creep stands on or next to. None when the creep is not next to the path
+    pub fn join(&self, pos: Position) -> Option<CachedPath> {
+        let positions = self.positions();
+        let (index, tile) = positions
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.get_range_to(pos) <= 1)
+            .min_by_key(|(i, p)| (p.get_range_to(pos), std::cmp::Reverse(*i)))?;
+        let mut steps = String::new();
+        if *tile != pos {
+            let d = pos.get_direction_to(*tile)?;
+            steps.push(char::from(b'0' + d as u8));
+        }
+        steps.extend(self.steps.chars().skip(index));
+        Some(CachedPath::new(
+            pos, self.dest, self.range, steps, self.time,
+        ))
+    }
+    // moves the start up to where the creep is, false when the creep left the path. walking
+    // onto an exit tile moves the creep into the next room in the same tick so it can be two
+    // steps further along than last tick
+    pub fn sync(&mut self, pos: Position) -> bool {
+        for _ in 0..3 {
+            if self.start == pos {
+                return true;
+            }
+            if !self.advance() {
+                return false;
+            }
+        }
+        false
+    }
+}
+
+// the directions from `start` along `path`, None when two positions are not next to each other
+pub fn serialize_path(start: Position, path: &[Position]) -> Option<String> {
+    let mut steps = String::with_capacity(path.len());
+    let mut prev = start;
+    for pos in path {
+        // stepping through a room exit goes from one edge tile to the next room's, which are
+        // next to each other in world coordinates
+        if prev.get_range_to(*pos) != 1 {
+            return None;
+        }
+        let d = prev.get_direction_to(*pos)?;
+        steps.push(char::from(b'0' + d as u8));
+        prev = *pos;
+    }
+    Some(steps)
+}
+
+// the direction a step of a serialized path stands for
+pub fn direction(c: char) -> Option<Direction> {
+    match c {
+        '1' => Some(Direction::Top),
+        '2' => Some(Direction::TopRight),
+        '3' => Some(Direction::Right),
+        '4' => Some(Direction::BottomRight),
+        '5' => Some(Direction::Bottom),
+        '6' => Some(Direction::BottomLeft),
+        '7' => Some(Direction::Left),
+        '8' => Some(Direction::TopLeft),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screeps::RoomCoordinate;
+    use std::str::FromStr;
+
+    fn pos(x: u8, y: u8, room: &str) -> Position {
+        Position::new(
+            RoomCoordinate::new(x).unwrap(),
+            RoomCoordinate::new(y).unwrap(),
+            RoomName::from_str(room).unwrap(),
+        )
+    }
+
+    // three steps right, one down right and one down
+    fn path() -> CachedPath {
+        let start = pos(10, 10, "W1N1");
+        let tiles = [(11, 10), (12, 10), (13, 10), (14, 11), (14, 12)];
+        let tiles: Vec<Position> = tiles.iter().map(|(x, y)| pos(*x, *y, "W1N1")).collect();
+        let steps = serialize_path(start, &tiles).unwrap();
+        CachedPath::new(start, pos(14, 14, "W1N1"), 1, steps, 100)
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        let path = path();
+        assert_eq!(path.steps, "33345");
+        assert_eq!(path.positions().len(), 6);
+        assert_eq!(path.positions()[5], pos(14, 12, "W1N1"));
+        assert_eq!(path.rooms, vec![RoomName::from_str("W1N1").unwrap()]);
+    }
+
+    #[test]
+    fn serialize_through_a_room_exit() {
+        // the west edge of W1N1 leads onto the east edge of W2N1
+        let start = pos(1, 20, "W1N1");
+        let tiles = [pos(0, 20, "W1N1"), pos(49, 20, "W2N1"), pos(48, 20, "W2N1")];
+        let steps = serialize_path(start, &tiles).unwrap();
+        assert_eq!(steps, "777");
+        let path = CachedPath::new(start, pos(40, 20, "W2N1"), 1, steps, 0);
+        assert_eq!(&path.positions()[1..], &tiles);
+        assert_eq!(
+            path.rooms,
+            vec![
+                RoomName::from_str("W1N1").unwrap(),
+                RoomName::from_str("W2N1").unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn serialize_needs_adjacent_tiles() {
+        let start = pos(10, 10, "W1N1");
+        assert_eq!(serialize_path(start, &[pos(12, 10, "W1N1")]), None);
+        assert_eq!(serialize_path(start, &[pos(10, 10, "W1N1")]), None);
+    }
+
+    #[test]
+    fn advance_and_sync() {
+        let mut walked = path();
+        assert_eq!(walked.next(), Some(Direction::Right));
+        assert!(walked.advance());
+        assert_eq!(walked.start, pos(11, 10, "W1N1"));
+        assert_eq!(walked.steps, "3345");
+        // the creep got two steps further, as it does walking through an exit
+        assert!(walked.sync(pos(13, 10, "W1N1")));
+        assert_eq!(walked.steps, "45");
+        // a creep that was pushed off the path does not find it again
+        assert!(!walked.sync(pos(20, 20, "W1N1")));
+        let mut other = path();
+        assert!(!other.sync(pos(10, 11, "W1N1")));
+        // nothing left to advance along
+        let mut end = CachedPath::new(
+            pos(10, 10, "W1N1"),
+            pos(10, 10, "W1N1"),
+            0,
+            String::new(),
+            0,
+        );
+        assert!(!end.advance());
+    }
+
+    #[test]
+    fn only_rested_steps_count_as_blocked() {
+        let mut path = path();
+        let start = path.start;
+        // tired, the creep did not ask to move
+        assert!(path.observe(start));
+        assert_eq!(path.blocked, 0);
+        path.stepped = true;
+        assert!(path.observe(start));
+        assert_eq!(path.blocked, 1);
+        assert!(!path.stepped);
+        // the step went through
+        path.stepped = true;
+        assert!(path.observe(pos(11, 10, "W1N1")));
+        assert_eq!(path.blocked, 0);
+    }
+
+    #[test]
+    fn join_at_the_nearest_step() {
+        let path = path();
+        // on the path
+        let joined = path.join(pos(12, 10, "W1N1")).unwrap();
+        assert_eq!(joined.start, pos(12, 10, "W1N1"));
+        assert_eq!(joined.steps, "345");
+        // next to two tiles of the path, the creep steps onto the one further along
+        let joined = path.join(pos(12, 11, "W1N1")).unwrap();
+        assert_eq!(joined.steps, "245");
+        assert_eq!(joined.positions()[1], pos(13, 10, "W1N1"));
+        assert_eq!(joined.dest, path.dest);
+        assert_eq!(joined.time, path.time);
+        // too far from the path
+        assert_eq!(path.join(pos(12, 12, "W1N1")), None);
+    }
+}
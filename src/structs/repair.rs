@@ -0,0 +1,160 @@
+use screeps::{ObjectId, Position, Structure, StructureType};
+
+// ramparts and walls have millions of hits, we only keep them up to this much per rcl
+const WALL_HITS_PER_RCL: [u32; 9] = [
+    0, 1_000, 10_000, 50_000, 100_000, 300_000, 1_000_000, 3_000_000, 10_000_000,
+];
+
+// hits a structure gets repaired up to
+pub fn repair_target(structure_type: StructureType, hits_max: u32, rcl: u8) -> u32 {
+    match structure_type {
+        StructureType::Rampart | StructureType::Wall => {
+            let idx = (rcl as usize).min(WALL_HITS_PER_RCL.len() - 1);
+            WALL_HITS_PER_RCL[idx].min(hits_max)
+        }
+        _ => hits_max,
+    }
+}
+
+// hits below which a structure starts asking for repairs, roads and containers decay all the
+// time so they wait until they are properly worn before anyone bothers
+pub fn repair_threshold(structure_type: StructureType, hits_max: u32, rcl: u8) -> u32 {
+    let target = repair_target(structure_type, hits_max, rcl);
+    match structure_type {
+        StructureType::Road => target / 2,
+        StructureType::Container => target * 3 / 4,
+        StructureType::Rampart | StructureType::Wall => target * 4 / 5,
+        _ => target,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DamagedStructure {
+    pub id: ObjectId<Structure>,
+    pub structure_type: StructureType,
+    pub pos: Position,
+    pub hits: u32,
+    pub target: u32,
+}
+
+impl DamagedStructure {
+    // how healthy the structure is compared to what we want it to be, 0 is about to break
+    pub fn ratio(&self) -> f64 {
+        match self.target {
+            0 => 1.0,
+            t => self.hits as f64 / t as f64,
+        }
+    }
+}
+
+// every structure of a room that is below its repair threshold, the most damaged first
+#[derive(Debug, Default, Clone)]
+pub struct RepairIndex {
+    pub damaged: Vec<DamagedStructure>,
+}
+
+#[allow(dead_code)]
+impl RepairIndex {
+    pub fn push(&mut self, structure: DamagedStructure) {
+        self.damaged.push(structure);
+        self.damaged.sort_by(|a, b| a.ratio().total_cmp(&b.ratio()));
+    }
+    pub fn len(&self) -> usize {
+        self.damaged.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.damaged.is_empty()
+    }
+    pub fn peek(&self) -> Option<&DamagedStructure> {
+        self.damaged.first()
+    }
+    pub fn remove(&mut self, id: ObjectId<Structure>) {
+        self.damaged.retain(|d| d.id != id);
+    }
+    // takes the structure out of the index so the next creep asking gets a different one.
+    // structures within a tenth of the worst ratio count as equally bad and the closest wins.
+    pub fn take_best(&mut self, pos: Position) -> Option<DamagedStructure> {
+        let worst = self.peek()?.ratio();
+        let index = self
+            .damaged
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| d.ratio() <= worst + 0.1)
+            .min_by_key(|(_, d)| d.pos.get_range_to(pos))
+            .map(|(i, _)| i)?;
+        Some(self.damaged.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screeps::{RawObjectId, RoomCoordinate, RoomName};
+    use std::str::FromStr;
+
+    fn damaged(n: u8, x: u8, hits: u32, target: u32) -> DamagedStructure {
+        let id = format!("5bbcab1d9099fc012e6342c{n}");
+        DamagedStructure {
+            id: RawObjectId::from_str(&id).unwrap().into(),
+            structure_type: StructureType::Road,
+            pos: Position::new(
+                RoomCoordinate::new(x).unwrap(),
+                RoomCoordinate::new(10).unwrap(),
+                RoomName::from_str("W1N1").unwrap(),
+            ),
+            hits,
+            target,
+        }
+    }
+
+    #[test]
+    fn walls_are_kept_to_the_rcl() {
+        for t in [StructureType::Wall, StructureType::Rampart] {
+            assert_eq!(repair_target(t, 300_000_000, 0), 0);
+            assert_eq!(repair_target(t, 300_000_000, 4), 100_000);
+            assert_eq!(repair_target(t, 300_000_000, 8), 10_000_000);
+            // never more than the structure holds
+            assert_eq!(repair_target(t, 300_000, 8), 300_000);
+            assert_eq!(repair_threshold(t, 300_000_000, 4), 80_000);
+        }
+    }
+
+    #[test]
+    fn other_structures_are_repaired_to_full() {
+        assert_eq!(repair_target(StructureType::Spawn, 5_000, 1), 5_000);
+        assert_eq!(repair_threshold(StructureType::Spawn, 5_000, 1), 5_000);
+        assert_eq!(repair_target(StructureType::Road, 5_000, 8), 5_000);
+        assert_eq!(repair_threshold(StructureType::Road, 5_000, 8), 2_500);
+        assert_eq!(
+            repair_threshold(StructureType::Container, 250_000, 3),
+            187_500
+        );
+    }
+
+    #[test]
+    fn index_keeps_the_most_damaged_first() {
+        let mut index = RepairIndex::default();
+        index.push(damaged(1, 10, 900, 1_000));
+        index.push(damaged(2, 10, 100, 1_000));
+        index.push(damaged(3, 10, 500, 1_000));
+        let hits: Vec<u32> = index.damaged.iter().map(|d| d.hits).collect();
+        assert_eq!(hits, vec![100, 500, 900]);
+        // nothing to repair up to counts as healthy
+        index.push(damaged(4, 10, 0, 0));
+        assert_eq!(index.damaged.last().map(|d| d.target), Some(0));
+    }
+
+    #[test]
+    fn take_best_prefers_the_closest_of_the_worst() {
+        let mut index = RepairIndex::default();
+        index.push(damaged(1, 40, 100, 1_000));
+        index.push(damaged(2, 12, 150, 1_000));
+        index.push(damaged(3, 11, 500, 1_000));
+        let here = damaged(0, 10, 0, 0).pos;
+        // the second is within a tenth of the worst and closer
+        assert_eq!(index.take_best(here).map(|d| d.hits), Some(150));
+        assert_eq!(index.take_best(here).map(|d| d.hits), Some(100));
+        assert_eq!(index.take_best(here).map(|d| d.hits), Some(500));
+        assert!(index.take_best(here).is_none());
+    }
+}
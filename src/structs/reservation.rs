@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+use screeps::RawObjectId;
+
+// what all creeps together have claimed of one object: harvest slots on a source, work on a
+// construction site, resources in a pickup or free space in a transfer target
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Reservation {
+    pub creeps: u32,
+    pub amount: u32,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Ledger {
+    pub reserved: HashMap<RawObjectId, Reservation>,
+}
+
+#[allow(dead_code)]
+impl Ledger {
+    pub fn reserve(&mut self, id: RawObjectId, amount: u32) {
+        let reservation = self.reserved.entry(id).or_default();
+        reservation.creeps += 1;
+        reservation.amount += amount;
+    }
+    pub fn release(&mut self, id: RawObjectId, amount: u32) {
+        if let Some(reservation) = self.reserved.get_mut(&id) {
+            reservation.creeps = reservation.creeps.saturating_sub(1);
+            reservation.amount = reservation.amount.saturating_sub(amount);
+            if reservation.creeps == 0 {
+                self.reserved.remove(&id);
+            }
+        }
+    }
+    pub fn get(&self, id: RawObjectId) -> Reservation {
+        self.reserved.get(&id).copied().unwrap_or_default()
+    }
+    // how much of `capacity` nobody has claimed yet
+    pub fn unreserved(&self, id: RawObjectId, capacity: u32) -> u32 {
+        capacity.saturating_sub(self.get(id).amount)
+    }
+}
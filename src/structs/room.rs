@@ -1,6 +1,9 @@
 use super::memory::RoomMemory;
-use screeps::{ConstructionSite, ObjectId, Source, StructureController, StructureSpawn};
+use screeps::{
+    ConstructionSite, ObjectId, RawObjectId, Source, StructureController, StructureSpawn,
+};
 
+#[allow(dead_code)]
 pub trait RoomExtend {
     fn get_sources(self) -> Vec<Source>;
     fn get_spawn(self) -> Vec<StructureSpawn>;
@@ -10,5 +13,5 @@ pub trait RoomExtend {
     fn is_mine(&self) -> bool;
     fn get_construction_sites(self) -> Vec<ConstructionSite>;
     fn get_controller_id(&self) -> Option<ObjectId<StructureController>>;
-    fn get_best_source(&self) -> Option<Source>;
+    fn get_best_source(&self, skip: &[RawObjectId]) -> Option<Source>;
 }
@@ -5,9 +5,11 @@ use screeps::RoomCoordinate;
 use screeps::Source;
 use screeps::Terrain;
 
+#[allow(dead_code)]
 pub trait SourceExtend {
     fn get_slots(self) -> Vec<Position>;
     fn get_free_slots(self) -> Vec<Position>;
+    fn get_walkable_slots(self) -> Vec<Position>;
     fn get_free_capacity(self) -> usize;
     fn get_used_slots(self) -> Vec<Position>;
 }
@@ -24,14 +26,14 @@ impl SourceExtend for Source {
                 slots.push((i, j));
             }
         }
+        let room = match self.room() {
+            Some(r) => r.name(),
+            None => return vec![],
+        };
         let mut positions = vec![];
         for (x, y) in slots {
-            let pos_x = RoomCoordinate::new(x);
-            let pos_y = RoomCoordinate::new(y);
-            if pos_x.is_ok() && pos_y.is_ok() {
-                let pos =
-                    Position::new(pos_x.unwrap(), pos_y.unwrap(), self.room().unwrap().name());
-                positions.push(pos);
+            if let (Ok(x), Ok(y)) = (RoomCoordinate::new(x), RoomCoordinate::new(y)) {
+                positions.push(Position::new(x, y, room));
             }
         }
         positions
@@ -41,26 +43,32 @@ impl SourceExtend for Source {
         //first get all the slots
         let slots = &self.clone().get_slots();
         let mut free = vec![];
-        let room = self.clone().room();
+        let room = match self.clone().room() {
+            Some(r) => r,
+            None => return free,
+        };
         for slot in slots {
             if room
-                .as_ref()
-                .unwrap()
                 .look_for_at_xy(look::CREEPS, slot.pos().x().0, slot.pos().y().0)
                 .is_empty()
-                && room
-                    .as_ref()
-                    .unwrap()
-                    .get_terrain()
-                    .get_xy(slot.pos().into())
-                    .to_owned()
-                    != Terrain::Wall
+                && room.get_terrain().get_xy(slot.pos().into()) != Terrain::Wall
             {
                 free.push(*slot);
             }
         }
         free
     }
+    // every slot a creep could harvest from, taken or not
+    fn get_walkable_slots(self) -> Vec<Position> {
+        let mut terrain = match self.room() {
+            Some(r) => r.get_terrain(),
+            None => return vec![],
+        };
+        self.get_slots()
+            .into_iter()
+            .filter(|slot| terrain.get_xy(slot.xy()) != Terrain::Wall)
+            .collect()
+    }
     fn get_free_capacity(self) -> usize {
         self.get_free_slots().len()
     }
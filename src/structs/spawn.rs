@@ -0,0 +1,222 @@
+use std::cmp::Reverse;
+
+use screeps::constants::Part;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    body::body_cost,
+    creep::{CreepMemory, CreepType},
+};
+
+// a single creep that a room wants spawned. requests are pushed by the room managers and roles
+// and are drained by the spawns of that room in priority order.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SpawnRequest {
+    pub role: CreepType,
+    pub body: Vec<Part>,
+    pub memory: CreepMemory,
+    // higher priority requests get spawned first
+    pub priority: u32,
+    // game tick after which the request is dropped if it has not been spawned yet
+    pub deadline: Option<u32>,
+}
+
+#[allow(dead_code)]
+impl SpawnRequest {
+    pub fn new(role: CreepType, body: Vec<Part>, memory: CreepMemory, priority: u32) -> Self {
+        SpawnRequest {
+            memory: memory.set_type(Some(role.clone())),
+            role,
+            body,
+            priority,
+            deadline: None,
+        }
+    }
+    pub fn set_deadline(mut self, deadline: Option<u32>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+    pub fn cost(&self) -> u32 {
+        body_cost(&self.body)
+    }
+    pub fn is_expired(&self, time: u32) -> bool {
+        match self.deadline {
+            Some(d) => time > d,
+            None => false,
+        }
+    }
+}
+
+// the queue is kept sorted by priority, requests with the same priority keep the order they were
+// pushed in.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct SpawnQueue {
+    pub requests: Vec<SpawnRequest>,
+}
+
+#[allow(dead_code)]
+impl SpawnQueue {
+    pub fn push(&mut self, request: SpawnRequest) {
+        self.requests.push(request);
+        // sort_by_key is stable so equal priorities stay first in first out
+        self.requests.sort_by_key(|r| Reverse(r.priority));
+    }
+    pub fn peek(&self) -> Option<&SpawnRequest> {
+        self.requests.first()
+    }
+    pub fn pop(&mut self) -> Option<SpawnRequest> {
+        if self.requests.is_empty() {
+            return None;
+        }
+        Some(self.requests.remove(0))
+    }
+    // the index of the most important request that matches the filter
+    pub fn find<F>(&self, filter: F) -> Option<usize>
+    where
+        F: Fn(&SpawnRequest) -> bool,
+    {
+        self.requests.iter().position(filter)
+    }
+    pub fn remove(&mut self, index: usize) -> Option<SpawnRequest> {
+        if index >= self.requests.len() {
+            return None;
+        }
+        Some(self.requests.remove(index))
+    }
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+    pub fn count_role(&self, role: &CreepType) -> usize {
+        self.requests.iter().filter(|r| r.role == *role).count()
+    }
+    // drops all requests that are past their deadline, returns how many were dropped
+    pub fn prune_expired(&mut self, time: u32) -> usize {
+        let before = self.requests.len();
+        self.requests.retain(|r| !r.is_expired(time));
+        before - self.requests.len()
+    }
+    // requests the room could never afford, like ones queued before it lost extensions, get
+    // the body `rebuild` makes for the capacity or are dropped when it makes none. returns how
+    // many were rebuilt and dropped
+    pub fn fit<F>(&mut self, capacity: u32, rebuild: F) -> (usize, usize)
+    where
+        F: Fn(&CreepType, u32) -> Option<Vec<Part>>,
+    {
+        let (mut rebuilt, mut dropped) = (0, 0);
+        self.requests.retain_mut(|r| {
+            if r.cost() <= capacity {
+                return true;
+            }
+            match rebuild(&r.role, capacity) {
+                Some(body) if body_cost(&body) <= capacity => {
+                    r.body = body;
+                    rebuilt += 1;
+                    true
+                }
+                _ => {
+                    dropped += 1;
+                    false
+                }
+            }
+        });
+        (rebuilt, dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(role: CreepType, priority: u32, deadline: Option<u32>) -> SpawnRequest {
+        SpawnRequest::new(
+            role,
+            vec![Part::Work, Part::Carry, Part::Move],
+            CreepMemory::default(),
+            priority,
+        )
+        .set_deadline(deadline)
+    }
+
+    fn roles(queue: &SpawnQueue) -> Vec<CreepType> {
+        queue.requests.iter().map(|r| r.role.clone()).collect()
+    }
+
+    #[test]
+    fn higher_priority_first() {
+        let mut queue = SpawnQueue::default();
+        queue.push(request(CreepType::Upgrader, 10, None));
+        queue.push(request(CreepType::Harvester, 30, None));
+        queue.push(request(CreepType::Builder, 20, None));
+        assert_eq!(
+            roles(&queue),
+            vec![
+                CreepType::Harvester,
+                CreepType::Builder,
+                CreepType::Upgrader
+            ]
+        );
+        assert_eq!(queue.pop().map(|r| r.role), Some(CreepType::Harvester));
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn same_priority_first_in_first_out() {
+        let mut queue = SpawnQueue::default();
+        queue.push(request(CreepType::Builder, 20, None));
+        queue.push(request(CreepType::Repairer, 20, None));
+        queue.push(request(CreepType::Harvester, 30, None));
+        queue.push(request(CreepType::Upgrader, 20, None));
+        assert_eq!(
+            roles(&queue),
+            vec![
+                CreepType::Harvester,
+                CreepType::Builder,
+                CreepType::Repairer,
+                CreepType::Upgrader,
+            ]
+        );
+    }
+
+    #[test]
+    fn expired_requests_are_pruned() {
+        let mut queue = SpawnQueue::default();
+        queue.push(request(CreepType::Builder, 20, Some(100)));
+        queue.push(request(CreepType::Hauler, 25, Some(200)));
+        queue.push(request(CreepType::Upgrader, 10, None));
+        assert_eq!(queue.prune_expired(100), 0);
+        assert_eq!(queue.prune_expired(101), 1);
+        assert_eq!(queue.prune_expired(10_000), 1);
+        assert_eq!(roles(&queue), vec![CreepType::Upgrader]);
+    }
+
+    #[test]
+    fn requests_are_fit_to_the_capacity() {
+        let mut queue = SpawnQueue::default();
+        let big = vec![Part::Work; 10];
+        queue.push(SpawnRequest::new(
+            CreepType::Upgrader,
+            big.clone(),
+            CreepMemory::default(),
+            10,
+        ));
+        queue.push(SpawnRequest::new(
+            CreepType::Claimer,
+            vec![Part::Claim, Part::Move],
+            CreepMemory::default(),
+            5,
+        ));
+        queue.push(request(CreepType::Builder, 20, None));
+        let rebuild = |role: &CreepType, _: u32| match role {
+            CreepType::Upgrader => Some(vec![Part::Work, Part::Move]),
+            _ => None,
+        };
+        assert_eq!(queue.fit(300, rebuild), (1, 1));
+        assert_eq!(roles(&queue), vec![CreepType::Builder, CreepType::Upgrader]);
+        assert_eq!(queue.requests[1].cost(), 150);
+        // everything fits now
+        assert_eq!(queue.fit(300, rebuild), (0, 0));
+    }
+}
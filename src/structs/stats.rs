@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 pub struct Stats {
     pub resrouces: Option<StatsResources>,
     pub performance: Option<StatPerformance>,
+    pub creeps: Option<StatCreeps>,
+    pub paths: Option<StatPaths>,
 }
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct StatPerformance {
@@ -20,3 +22,22 @@ pub struct StatsResources {
     pub cpu: Option<u128>,
     pub credits: Option<u128>,
 }
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct StatCreeps {
+    // creeps that have not moved or worked for a few ticks
+    pub stuck: Option<u32>,
+    // targets given up on this tick
+    pub abandoned: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct StatPaths {
+    // pathfinder searches this tick and the cpu they used
+    pub searches: Option<u32>,
+    pub cpu: Option<f64>,
+    // moves that came from a cached path instead of a search
+    pub reused: Option<u32>,
+    // estimated cpu the reused paths saved
+    pub saved: Option<f64>,
+}
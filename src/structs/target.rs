@@ -1,300 +1,691 @@
-use log::{error, trace};
+use log::{debug, error, trace};
 use screeps::{
-    ConstructionSite, Creep, ErrorCode, HasId, ObjectId, ResourceType, SharedCreepProperties,
-    Source, StructureController,
+    game, ConstructionSite, Creep, ErrorCode, HasId, HasPosition, ObjectId, Position, RawObjectId,
+    Resource, ResourceType, RoomObjectProperties, Ruin, SharedCreepProperties, Source, Structure,
+    StructureController, StructureObject, Tombstone, Transferable, Withdrawable,
 };
 use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
 
-use crate::{managment::creep::CreepExtend as _, structs::room::RoomExtend};
-
-
+use crate::{
+    managment::{creep::CreepExtend as _, intents},
+    structs::{
+        intent::{Conflict, IntentKind},
+        repair::repair_target,
+        room::RoomExtend,
+    },
+};
 
 // this enum will represent a creep's lock on a specific target object, storing a js reference
 // to the object id so that we can grab a fresh reference to the object each successive tick,
 // since screeps game objects become 'stale' and shouldn't be used beyond the tick they were fetched
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum CreepTarget {
     Upgrade(ObjectId<StructureController>),
     Harvest(ObjectId<Source>),
     Spawn(ObjectId<screeps::StructureSpawn>),
     Build(ObjectId<ConstructionSite>),
+    Repair(ObjectId<Structure>),
+    // take a resource out of any structure with a store, a tombstone or a ruin
+    Withdraw(RawObjectId, ResourceType),
+    Pickup(ObjectId<Resource>),
+    // put a resource into any structure with a store
+    Transfer(RawObjectId, ResourceType),
 }
 impl CreepTarget {
-    pub fn run(self, creep: &Creep) -> bool {
+    // where the creep does its work for this target, None if the object is gone or not visible
+    pub fn pos(&self) -> Option<Position> {
         match self {
-            CreepTarget::Build(object_id) => match object_id.resolve() {
-                Some(target) => {
-                    let res = creep.build(&target);
-                    if let Err(errorcode) = res {
-                        match errorcode {
-                            ErrorCode::NotFound => {
-                                let res = creep.set_target(None);
-                                match res {
-                                    Err(e) => {
-                                        error!("could not set target: {e}");
-                                        return false;
-                                    }
-                                    Ok(_) => {
-                                        trace!("sucessfully set target of creep: {}", creep.name());
-                                        return true;
-                                    }
-                                }
-                            }
-                            ErrorCode::NotEnough => {
-                                let res = creep.set_target(None);
-                                match res {
-                                    Err(e) => {
-                                        error!("could not set target: {e}");
-                                        return false;
-                                    }
-                                    Ok(_) => {
-                                        trace!("sucessfully set target of creep: {}", creep.name());
-                                        return true;
-                                    }
-                                }
-                            }
-                            ErrorCode::NotInRange => {
-                                let res = creep.b_move(target);
-                                match res {
-                                    Err(e) => {
-                                        error!("could not set target: {e:?}");
-                                        return false;
-                                    }
-                                    Ok(_) => {
-                                        trace!("sucessfully set target of creep: {}", creep.name());
-                                        return true;
-                                    }
-                                }
-                            }
-                            _e => {
-                                error!("error building {_e:?}");
-                                return false;
-                            }
-                        };
-                    };
-                    return false;
-                }
-                None => {
-                    return false;
-                }
+            CreepTarget::Upgrade(id) => id.resolve().map(|o| o.pos()),
+            CreepTarget::Harvest(id) => id.resolve().map(|o| o.pos()),
+            CreepTarget::Spawn(id) => id.resolve().map(|o| o.pos()),
+            CreepTarget::Build(id) => id.resolve().map(|o| o.pos()),
+            CreepTarget::Repair(id) => id.resolve().map(|o| o.pos()),
+            CreepTarget::Withdraw(id, _) => game::get_object_by_id_erased(id).map(|o| o.pos()),
+            CreepTarget::Pickup(id) => id.resolve().map(|o| o.pos()),
+            CreepTarget::Transfer(id, _) => game::get_object_by_id_erased(id).map(|o| o.pos()),
+        }
+    }
+    // the object the target points at, reservations are kept under this id
+    pub fn object_id(&self) -> RawObjectId {
+        match self {
+            CreepTarget::Upgrade(id) => (*id).into(),
+            CreepTarget::Harvest(id) => (*id).into(),
+            CreepTarget::Spawn(id) => (*id).into(),
+            CreepTarget::Build(id) => (*id).into(),
+            CreepTarget::Repair(id) => (*id).into(),
+            CreepTarget::Withdraw(id, _) => *id,
+            CreepTarget::Pickup(id) => (*id).into(),
+            CreepTarget::Transfer(id, _) => *id,
+        }
+    }
+    // how long a creep may spend on the target in total and without making any progress
+    // before it gives up on it
+    pub fn limits(&self, limits: &TargetLimits) -> Limits {
+        let (max_ticks, max_idle) = match self {
+            CreepTarget::Upgrade(_) => (limits.upgrade_ticks, limits.upgrade_idle),
+            CreepTarget::Harvest(_) => (limits.harvest_ticks, limits.harvest_idle),
+            CreepTarget::Build(_) | CreepTarget::Repair(_) => (limits.work_ticks, limits.work_idle),
+            CreepTarget::Spawn(_)
+            | CreepTarget::Withdraw(_, _)
+            | CreepTarget::Pickup(_)
+            | CreepTarget::Transfer(_, _) => (limits.haul_ticks, limits.haul_idle),
+        };
+        Limits {
+            max_ticks,
+            max_idle,
+        }
+    }
+
+    // how close the creep has to be to work on the target
+    pub fn range(&self) -> u32 {
+        match self.transitions().not_in_range {
+            Transition::Move(range) => range,
+            _ => 1,
+        }
+    }
+
+    // what the creep does after an action on this target came back with the given outcome.
+    // every transition of every target is declared here, `run` only executes them.
+    pub fn transitions(&self) -> Transitions {
+        use Transition::*;
+        match self {
+            CreepTarget::Upgrade(_) => Transitions {
+                ok: Stay,
+                not_in_range: Move(3),
+                full: Stay,
+                not_enough: Done,
+                not_found: Done,
+                no_path: Done,
             },
-            CreepTarget::Harvest(object_id) => {
-                //kay we have a target lets move to it.
-                if creep.is_full() {
-                    //we are full so lets set the target to none
-                    let res = creep.set_target(None);
-                    match res {
-                        Err(e) => {
-                            error!("could not set target: {e}");
-                            return false;
-                        }
-                        Ok(_) => {
-                            trace!("sucessfully set target of creep: {}", creep.name());
-                            return true;
-                        }
-                    }
+            // a source that is empty or out of reach sends the creep to the best other one
+            CreepTarget::Harvest(_) => Transitions {
+                ok: Stay,
+                not_in_range: Move(1),
+                full: Done,
+                not_enough: Harvest,
+                not_found: Done,
+                no_path: Harvest,
+            },
+            // a full spawn does not need the energy, the controller always does
+            CreepTarget::Spawn(_) => Transitions {
+                ok: Done,
+                not_in_range: Move(1),
+                full: Upgrade,
+                not_enough: Harvest,
+                not_found: Done,
+                no_path: Done,
+            },
+            CreepTarget::Build(_) => Transitions {
+                ok: Stay,
+                not_in_range: Move(3),
+                full: Stay,
+                not_enough: Done,
+                not_found: Done,
+                no_path: Done,
+            },
+            // full means the structure is repaired up to its target
+            CreepTarget::Repair(_) => Transitions {
+                ok: Stay,
+                not_in_range: Move(3),
+                full: Done,
+                not_enough: Done,
+                not_found: Done,
+                no_path: Done,
+            },
+            // one withdraw, pickup or transfer moves all that fits so they are done right away
+            CreepTarget::Withdraw(_, _) | CreepTarget::Pickup(_) | CreepTarget::Transfer(_, _) => {
+                Transitions {
+                    ok: Done,
+                    not_in_range: Move(1),
+                    full: Done,
+                    not_enough: Done,
+                    not_found: Done,
+                    no_path: Done,
                 }
-                match object_id.resolve() {
-                    Some(source) => {
-                        // creep.room().unwrap().visual().line((creep.pos().x().0.into(), creep.pos().y().0.into()), (source.pos().x().0.into(), source.pos().y().0.into()), None);
-
-                        let res = creep.harvest(&source);
-                        match res {
-                            Err(e) => match e {
-                                ErrorCode::Full => {}
-                                ErrorCode::NotInRange => {
-                                    let res = creep.b_move(source);
-
-                                    match res {
-                                        Err(e) => {
-                                            match e {
-                                                ErrorCode::NoPath => {
-                                                    error!("no path to target");
-                                                    let best =
-                                                        creep.room().unwrap().get_best_source();
-                                                    if let Some(source) = best {
-                                                        let res = creep.set_target(Some(
-                                                            CreepTarget::Harvest(source.id()),
-                                                        ));
-                                                    }
-                                                    return false;
-                                                }
-                                                _e => {
-                                                    error!("error moving: {} {e:?}", creep.name())
-                                                }
-                                            }
-
-                                            return false;
-                                        }
-                                        Ok(_) => {
-                                            //is there a way to see if a path is avalibe?
-                                            trace!("moved to target: {}", creep.name());
-                                            return true;
-                                        }
-                                    }
-                                }
-                                ErrorCode::NoPath => {
-                                    error!("no path to target");
-                                    let best = creep.room().unwrap().get_best_source();
-                                    error!("best source: {:?}", best);
-                                    if let Some(source) = best {
-                                        creep.set_target(Some(CreepTarget::Harvest(source.id())));
-                                    }
-                                    return false;
-                                }
-                                _ => {
-                                    error!("error while harvesting: {e:?}");
-                                    return true;
-                                }
-                            },
-                            Ok(_) => {
-                                return true;
-                            }
-                        }
-                    }
-                    None => {
-                        return false;
-                    }
-                };
-                return false;
             }
-            CreepTarget::Spawn(object_id) => match object_id.resolve() {
-                Some(spawn) => {
-                    let res = creep.transfer(&spawn, ResourceType::Energy, creep.get_energy());
-                    match res {
-                        Ok(_) => {
-                            let res = creep.set_target(None);
-                            match res {
-                                Err(e) => {
-                                    error!("could not set target: {e}");
-                                    return false;
-                                }
-                                Ok(_) => {
-                                    trace!("sucessfully set target of creep: {}", creep.name());
-                                    return true;
-                                }
-                            }
-                        }
-                        Err(error) => match error {
-                            ErrorCode::NotEnough => {
-                                let res = creep.set_target(Some(CreepTarget::Harvest(
-                                    creep.room().unwrap().get_best_source().unwrap().id(),
-                                )));
-                                match res {
-                                    Err(e) => {
-                                        error!("could not set target: {e}");
-                                        return false;
-                                    }
-                                    Ok(_) => {
-                                        trace!("sucessfully set target of creep: {}", creep.name());
-                                        return true;
-                                    }
-                                }
-                            }
-
-                            ErrorCode::Full => {
-                                let res = creep.set_target(Some(CreepTarget::Upgrade(
-                                    creep.room().unwrap().controller().unwrap().id(),
-                                )));
-                                match res {
-                                    Err(e) => {
-                                        error!("could not set target: {e}");
-                                        return false;
-                                    }
-                                    Ok(_) => {
-                                        trace!("sucessfully set target of creep: {}", creep.name());
-                                        return true;
-                                    }
-                                }
-                            }
-                            ErrorCode::NotInRange => {
-                                let res = creep.b_move(spawn);
-                                match res {
-                                    Err(e) => {
-                                        error!("could not move to spawn: {e:?}");
-                                        return false;
-                                    }
-                                    Ok(_) => {
-                                        trace!("sucessfully set target of creep: {}", creep.name());
-                                        return true;
-                                    }
-                                }
-                            }
-                            _ => {
-                                error!("some kind of error happened: {error:?}");
-                                return false;
-                            }
-                        },
-                    }
+        }
+    }
+
+    // does the target's action and follows the transition for its outcome
+    pub fn run(self, creep: &Creep) -> bool {
+        self.execute(creep, false)
+    }
+
+    fn execute(self, creep: &Creep, chained: bool) -> bool {
+        // an intent issued earlier this tick would win over this one, get in range so the
+        // action can happen next tick
+        if let Conflict::Blocked(_) = intents::conflict(creep, self.intent()) {
+            return match (self.pos(), self.transitions().not_in_range) {
+                (Some(pos), Transition::Move(range)) if !creep.pos().in_range_to(pos, range) => {
+                    creep.b_move_range(pos, range).is_ok()
                 }
-                None => {
-                    error!("spawn not set?");
-                    return false;
+                _ => true,
+            };
+        }
+        let (result, pos) = self.act(creep);
+        let outcome = Outcome::from(result);
+        if outcome == Outcome::Ok {
+            intents::record(creep, self.intent());
+        }
+        let decision = self.transitions().decide(outcome, pos, chained);
+        trace!("{} {outcome:?} on {self:?}, {decision:?}", creep.name());
+        match decision {
+            Decision::Spawning => {
+                trace!("{} is still spawning", creep.name());
+                false
+            }
+            Decision::Failed(outcome) => {
+                error!("{} failed on {self:?}: {outcome:?}", creep.name());
+                false
+            }
+            Decision::Wait => true,
+            Decision::Move(pos, range) => match creep.b_move_range(pos, range) {
+                Ok(_) => true,
+                Err(ErrorCode::NoPath) => {
+                    debug!("{} has no path to {pos}", creep.name());
+                    self.follow(self.transitions().no_path, creep)
+                }
+                Err(e) => {
+                    debug!("{} could not move to {pos}: {e:?}", creep.name());
+                    false
+                }
+            },
+            Decision::Follow(t) => self.follow(t, creep),
+        }
+    }
+
+    // a finished target hands over to the next task in the creep's queue, which gets to act in
+    // the same tick
+    fn follow(&self, transition: Transition, creep: &Creep) -> bool {
+        match transition {
+            Transition::Done => match creep.next_task() {
+                Ok(Some(next)) => {
+                    trace!("{} moves on to {next:?}", creep.name());
+                    next.execute(creep, true)
+                }
+                Ok(None) => true,
+                Err(e) => {
+                    error!("could not advance task queue of {}: {e}", creep.name());
+                    false
                 }
             },
-            CreepTarget::Upgrade(object_id) => {
-                match object_id.resolve() {
-                    Some(controller) => {
-                        let res = creep.upgrade_controller(&controller);
-                        match res {
-                            Ok(_) => {
-                                trace!("we upgraded the controller bois");
-                                return true;
-                            }
-                            Err(error) => {
-                                match error {
-                                    ErrorCode::NotEnough => {
-                                        let res = creep.set_target(None);
-                                        match res {
-                                            Err(e) => {
-                                                error!("could not set target: {e}");
-                                                return false;
-                                            }
-                                            Ok(_) => {
-                                                trace!(
-                                                    "sucessfully set target of creep: {}",
-                                                    creep.name()
-                                                );
-                                                return true;
-                                            }
-                                        }
-                                    } // maybe get some energy?
-                                    ErrorCode::NotInRange => {
-                                        let res = creep.b_move(controller);
-                                        match res {
-                                            Err(e) => {
-                                                error!("could not move to:  {e:?}");
-                                                return false;
-                                            }
-                                            Ok(_) => {
-                                                trace!(
-                                                    "creep moved to controller {}",
-                                                    creep.name()
-                                                );
-                                                return true;
-                                            }
-                                        }
-                                    } // fucking move to it
-                                    ErrorCode::Busy => {
-                                        trace!("seems like creep is spawning");
-                                        return false;
-                                    }
-                                    _ => {
-                                        error!(
-                                            "unknown error for upgrading the controller: {error:?}"
-                                        );
-                                        return false;
-                                    }
-                                }
-                            }
-                        }
+            t => t.apply(creep),
+        }
+    }
+
+    // the intent the target's action issues
+    pub fn intent(&self) -> IntentKind {
+        match self {
+            CreepTarget::Upgrade(_) => IntentKind::Upgrade,
+            CreepTarget::Harvest(_) => IntentKind::Harvest,
+            CreepTarget::Spawn(_) => IntentKind::Transfer,
+            CreepTarget::Build(_) => IntentKind::Build,
+            CreepTarget::Repair(_) => IntentKind::Repair,
+            CreepTarget::Withdraw(_, _) => IntentKind::Withdraw,
+            CreepTarget::Pickup(_) => IntentKind::Pickup,
+            CreepTarget::Transfer(_, _) => IntentKind::Transfer,
+        }
+    }
+
+    // the intent of the target, checks that the game would not report (a harvester that is full
+    // still harvests and drops the energy) are turned into the error the action would have had
+    fn act(&self, creep: &Creep) -> (Result<(), ErrorCode>, Option<Position>) {
+        match self {
+            CreepTarget::Upgrade(id) => match id.resolve() {
+                Some(c) => (creep.upgrade_controller(&c), Some(c.pos())),
+                None => (Err(ErrorCode::NotFound), None),
+            },
+            CreepTarget::Harvest(id) => match id.resolve() {
+                _ if creep.is_full() => (Err(ErrorCode::Full), None),
+                Some(s) => (creep.harvest(&s), Some(s.pos())),
+                None => (Err(ErrorCode::NotFound), None),
+            },
+            CreepTarget::Spawn(id) => match id.resolve() {
+                Some(s) => (
+                    creep.transfer(&s, ResourceType::Energy, creep.get_energy()),
+                    Some(s.pos()),
+                ),
+                None => (Err(ErrorCode::NotFound), None),
+            },
+            CreepTarget::Build(id) => match id.resolve() {
+                Some(s) => (creep.build(&s), Some(s.pos())),
+                None => (Err(ErrorCode::NotFound), None),
+            },
+            CreepTarget::Repair(id) => match id.resolve() {
+                Some(structure) => {
+                    let pos = structure.pos();
+                    let rcl = match structure.room().and_then(|r| r.controller()) {
+                        Some(c) => c.level(),
+                        None => 0,
+                    };
+                    let target_hits =
+                        repair_target(structure.structure_type(), structure.hits_max(), rcl);
+                    if structure.hits() >= target_hits {
+                        return (Err(ErrorCode::Full), Some(pos));
                     }
-                    None => {
-                        error!("seems like the controller does not exist");
-                        return false;
+                    let object = StructureObject::from(structure);
+                    match object.as_repairable() {
+                        Some(r) => (creep.repair(r), Some(pos)),
+                        None => (Err(ErrorCode::InvalidTarget), Some(pos)),
                     }
                 }
+                None => (Err(ErrorCode::NotFound), None),
+            },
+            CreepTarget::Withdraw(_, _) if creep.is_full() => (Err(ErrorCode::Full), None),
+            CreepTarget::Withdraw(id, resource) => with_withdrawable(*id, |target, pos| {
+                (creep.withdraw(target, *resource, None), Some(pos))
+            })
+            .unwrap_or((Err(ErrorCode::NotFound), None)),
+            CreepTarget::Pickup(id) => match id.resolve() {
+                Some(r) => (creep.pickup(&r), Some(r.pos())),
+                None => (Err(ErrorCode::NotFound), None),
+            },
+            CreepTarget::Transfer(_, resource)
+                if creep.store().get_used_capacity(Some(*resource)) == 0 =>
+            {
+                (Err(ErrorCode::NotEnough), None)
+            }
+            CreepTarget::Transfer(id, resource) => with_transferable(*id, |target, pos| {
+                (creep.transfer(target, *resource, None), Some(pos))
+            })
+            .unwrap_or((Err(ErrorCode::NotFound), None)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub max_ticks: u32,
+    pub max_idle: u32,
+}
+
+// the limits of every kind of target in a room, set from the console like
+// Memory.rooms.W1N1.target_limits.harvest_idle = 40. work is building and repairing, haul is
+// filling spawns and moving resources around
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct TargetLimits {
+    pub upgrade_ticks: u32,
+    pub upgrade_idle: u32,
+    pub harvest_ticks: u32,
+    pub harvest_idle: u32,
+    pub work_ticks: u32,
+    pub work_idle: u32,
+    pub haul_ticks: u32,
+    pub haul_idle: u32,
+}
+
+impl Default for TargetLimits {
+    fn default() -> Self {
+        TargetLimits {
+            upgrade_ticks: 1_000,
+            upgrade_idle: 25,
+            harvest_ticks: 300,
+            harvest_idle: 25,
+            work_ticks: 500,
+            work_idle: 25,
+            haul_ticks: 150,
+            haul_idle: 15,
+        }
+    }
+}
+
+// what an action on a target came back with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Ok,
+    NotInRange,
+    Full,
+    NotEnough,
+    NotFound,
+    NoPath,
+    Failed(ErrorCode),
+}
+
+impl From<Result<(), ErrorCode>> for Outcome {
+    fn from(result: Result<(), ErrorCode>) -> Self {
+        match result {
+            Ok(_) => Outcome::Ok,
+            Err(ErrorCode::NotInRange) => Outcome::NotInRange,
+            Err(ErrorCode::Full) => Outcome::Full,
+            Err(ErrorCode::NotEnough) => Outcome::NotEnough,
+            Err(ErrorCode::NotFound) | Err(ErrorCode::InvalidTarget) => Outcome::NotFound,
+            Err(ErrorCode::NoPath) => Outcome::NoPath,
+            Err(e) => Outcome::Failed(e),
+        }
+    }
+}
+
+// what a creep does with its target after an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    // keep the target and do the same next tick
+    Stay,
+    // walk until in this range of the target
+    Move(u32),
+    // drop the target, the role picks a new one
+    Done,
+    // go harvest the room's best source instead
+    Harvest,
+    // go upgrade the room's controller instead
+    Upgrade,
+}
+
+impl Transition {
+    fn apply(self, creep: &Creep) -> bool {
+        let room = creep.room();
+        let next = match self {
+            Transition::Stay | Transition::Move(_) => return true,
+            Transition::Done => None,
+            Transition::Harvest => room
+                .and_then(|r| r.get_best_source(&creep.get_blacklist()))
+                .map(|s| CreepTarget::Harvest(s.id())),
+            Transition::Upgrade => room
+                .and_then(|r| r.controller())
+                .filter(|c| !creep.avoids(c.id().into()))
+                .map(|c| CreepTarget::Upgrade(c.id())),
+        };
+        match creep.set_target(next) {
+            Err(e) => {
+                error!("could not set target: {e}");
+                false
+            }
+            Ok(_) => {
+                trace!("sucessfully set target of creep: {}", creep.name());
+                true
             }
         }
     }
 }
+
+// the transition of a target for every outcome, other failures are logged and the creep keeps
+// its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transitions {
+    pub ok: Transition,
+    pub not_in_range: Transition,
+    pub full: Transition,
+    pub not_enough: Transition,
+    pub not_found: Transition,
+    pub no_path: Transition,
+}
+
+impl Transitions {
+    pub fn on(&self, outcome: &Outcome) -> Option<Transition> {
+        match outcome {
+            Outcome::Ok => Some(self.ok),
+            Outcome::NotInRange => Some(self.not_in_range),
+            Outcome::Full => Some(self.full),
+            Outcome::NotEnough => Some(self.not_enough),
+            Outcome::NotFound => Some(self.not_found),
+            Outcome::NoPath => Some(self.no_path),
+            Outcome::Failed(_) => None,
+        }
+    }
+}
+
+// what the executor does after a target's action came back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    // the creep is still spawning and cannot act yet
+    Spawning,
+    // no transition covers the outcome, the creep keeps its target
+    Failed(Outcome),
+    // a chained task waits a tick for what the previous one did to show up
+    Wait,
+    // walk until in this range of the target's position
+    Move(Position, u32),
+    // take the transition, `Done` hands over to the next task in the queue
+    Follow(Transition),
+}
+
+impl Transitions {
+    // the decision for an outcome, `pos` is where the target is if the action found it
+    pub fn decide(&self, outcome: Outcome, pos: Option<Position>, chained: bool) -> Decision {
+        let transition = match self.on(&outcome) {
+            Some(t) => t,
+            None if outcome == Outcome::Failed(ErrorCode::Busy) => return Decision::Spawning,
+            None => return Decision::Failed(outcome),
+        };
+        // a task that follows another one in the same tick may need what the previous one
+        // withdrew or freed, which only shows up next tick
+        if chained && matches!(outcome, Outcome::NotEnough | Outcome::Full) {
+            return Decision::Wait;
+        }
+        match (transition, pos) {
+            (Transition::Move(range), Some(pos)) => Decision::Move(pos, range),
+            (t, _) => Decision::Follow(t),
+        }
+    }
+}
+
+// looks up anything a creep can withdraw from: structures with a store, tombstones and ruins
+fn with_withdrawable<F, R>(id: RawObjectId, f: F) -> Option<R>
+where
+    F: FnOnce(&dyn Withdrawable, Position) -> R,
+{
+    let object = game::get_object_by_id_erased(&id)?;
+    let pos = object.pos();
+    if let Some(structure) = object.dyn_ref::<Structure>() {
+        let structure = StructureObject::from(structure.clone());
+        return structure.as_withdrawable().map(|w| f(w, pos));
+    }
+    if let Some(tombstone) = object.dyn_ref::<Tombstone>() {
+        return Some(f(tombstone, pos));
+    }
+    if let Some(ruin) = object.dyn_ref::<Ruin>() {
+        return Some(f(ruin, pos));
+    }
+    None
+}
+
+// looks up a structure a creep can transfer into
+fn with_transferable<F, R>(id: RawObjectId, f: F) -> Option<R>
+where
+    F: FnOnce(&dyn Transferable, Position) -> R,
+{
+    let object = game::get_object_by_id_erased(&id)?;
+    let pos = object.pos();
+    let structure = StructureObject::from(object.dyn_ref::<Structure>()?.clone());
+    structure.as_transferable().map(|t| f(t, pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screeps::{RoomCoordinate, RoomName};
+    use std::str::FromStr;
+
+    fn raw() -> RawObjectId {
+        RawObjectId::from_str("5bbcab1d9099fc012e6342c9").unwrap()
+    }
+
+    // every outcome in the order ok, not in range, full, not enough, not found, no path
+    fn table(target: &CreepTarget) -> Vec<Option<Transition>> {
+        let t = target.transitions();
+        [
+            Outcome::Ok,
+            Outcome::NotInRange,
+            Outcome::Full,
+            Outcome::NotEnough,
+            Outcome::NotFound,
+            Outcome::NoPath,
+        ]
+        .iter()
+        .map(|o| t.on(o))
+        .collect()
+    }
+
+    fn expect(transitions: [Transition; 6]) -> Vec<Option<Transition>> {
+        transitions.into_iter().map(Some).collect()
+    }
+
+    use Transition::*;
+
+    #[test]
+    fn upgrade_transitions() {
+        let target = CreepTarget::Upgrade(raw().into());
+        assert_eq!(
+            table(&target),
+            expect([Stay, Move(3), Stay, Done, Done, Done])
+        );
+    }
+
+    #[test]
+    fn harvest_transitions() {
+        let target = CreepTarget::Harvest(raw().into());
+        assert_eq!(
+            table(&target),
+            expect([Stay, Move(1), Done, Harvest, Done, Harvest])
+        );
+    }
+
+    #[test]
+    fn spawn_transitions() {
+        let target = CreepTarget::Spawn(raw().into());
+        assert_eq!(
+            table(&target),
+            expect([Done, Move(1), Upgrade, Harvest, Done, Done])
+        );
+    }
+
+    #[test]
+    fn build_transitions() {
+        let target = CreepTarget::Build(raw().into());
+        assert_eq!(
+            table(&target),
+            expect([Stay, Move(3), Stay, Done, Done, Done])
+        );
+    }
+
+    #[test]
+    fn repair_transitions() {
+        let target = CreepTarget::Repair(raw().into());
+        assert_eq!(
+            table(&target),
+            expect([Stay, Move(3), Done, Done, Done, Done])
+        );
+    }
+
+    #[test]
+    fn resource_transitions() {
+        let targets = [
+            CreepTarget::Withdraw(raw(), ResourceType::Energy),
+            CreepTarget::Pickup(raw().into()),
+            CreepTarget::Transfer(raw(), ResourceType::Energy),
+        ];
+        for target in targets {
+            assert_eq!(
+                table(&target),
+                expect([Done, Move(1), Done, Done, Done, Done]),
+                "{target:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn other_failures_have_no_transition() {
+        let target = CreepTarget::Upgrade(raw().into());
+        let outcome = Outcome::Failed(ErrorCode::Busy);
+        assert_eq!(target.transitions().on(&outcome), None);
+    }
+
+    fn pos() -> Position {
+        Position::new(
+            RoomCoordinate::new(10).unwrap(),
+            RoomCoordinate::new(20).unwrap(),
+            RoomName::from_str("W1N1").unwrap(),
+        )
+    }
+
+    #[test]
+    fn decision_for_every_error_code() {
+        use ErrorCode::*;
+        // building: stay while out of energy to spend, walk into range and drop the target when
+        // it is gone or unreachable
+        let target = CreepTarget::Build(raw().into());
+        let cases = [
+            (NotOwner, Decision::Failed(Outcome::Failed(NotOwner))),
+            (NoPath, Decision::Follow(Done)),
+            (NameExists, Decision::Failed(Outcome::Failed(NameExists))),
+            (Busy, Decision::Spawning),
+            (NotFound, Decision::Follow(Done)),
+            (NotEnough, Decision::Follow(Done)),
+            (InvalidTarget, Decision::Follow(Done)),
+            (Full, Decision::Follow(Stay)),
+            (NotInRange, Decision::Move(pos(), 3)),
+            (InvalidArgs, Decision::Failed(Outcome::Failed(InvalidArgs))),
+            (Tired, Decision::Failed(Outcome::Failed(Tired))),
+            (NoBodypart, Decision::Failed(Outcome::Failed(NoBodypart))),
+            (
+                RclNotEnough,
+                Decision::Failed(Outcome::Failed(RclNotEnough)),
+            ),
+            (
+                GclNotEnough,
+                Decision::Failed(Outcome::Failed(GclNotEnough)),
+            ),
+        ];
+        for (code, decision) in cases {
+            let outcome = Outcome::from(Err(code));
+            assert_eq!(
+                target.transitions().decide(outcome, Some(pos()), false),
+                decision,
+                "{code:?}"
+            );
+        }
+        assert_eq!(
+            target.transitions().decide(Outcome::Ok, Some(pos()), false),
+            Decision::Follow(Stay)
+        );
+    }
+
+    #[test]
+    fn chained_tasks_wait_for_resources() {
+        let target = CreepTarget::Transfer(raw(), ResourceType::Energy);
+        let t = target.transitions();
+        for code in [ErrorCode::NotEnough, ErrorCode::Full] {
+            let outcome = Outcome::from(Err(code));
+            assert_eq!(t.decide(outcome, Some(pos()), true), Decision::Wait);
+            assert_eq!(
+                t.decide(outcome, Some(pos()), false),
+                Decision::Follow(Done)
+            );
+        }
+        // chaining only delays the resource outcomes
+        let outcome = Outcome::from(Err(ErrorCode::NotInRange));
+        assert_eq!(
+            t.decide(outcome, Some(pos()), true),
+            Decision::Move(pos(), 1)
+        );
+    }
+
+    #[test]
+    fn move_without_a_position_keeps_the_target() {
+        let target = CreepTarget::Upgrade(raw().into());
+        let outcome = Outcome::NotInRange;
+        assert_eq!(
+            target.transitions().decide(outcome, None, false),
+            Decision::Follow(Move(3))
+        );
+    }
+
+    #[test]
+    fn outcome_from_result() {
+        let cases = [
+            (Ok(()), Outcome::Ok),
+            (Err(ErrorCode::NotInRange), Outcome::NotInRange),
+            (Err(ErrorCode::Full), Outcome::Full),
+            (Err(ErrorCode::NotEnough), Outcome::NotEnough),
+            (Err(ErrorCode::NotFound), Outcome::NotFound),
+            (Err(ErrorCode::InvalidTarget), Outcome::NotFound),
+            (Err(ErrorCode::NoPath), Outcome::NoPath),
+            (Err(ErrorCode::Busy), Outcome::Failed(ErrorCode::Busy)),
+            (Err(ErrorCode::Tired), Outcome::Failed(ErrorCode::Tired)),
+        ];
+        for (result, outcome) in cases {
+            assert_eq!(Outcome::from(result), outcome, "{result:?}");
+        }
+    }
+}
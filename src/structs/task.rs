@@ -0,0 +1,34 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::target::CreepTarget;
+
+// the targets a creep works through after its current one, so a role can plan a whole route
+// ("withdraw from storage, then fill these extensions") up front
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TaskQueue {
+    pub tasks: VecDeque<CreepTarget>,
+}
+
+#[allow(dead_code)]
+impl TaskQueue {
+    pub fn push(&mut self, task: CreepTarget) {
+        self.tasks.push_back(task);
+    }
+    pub fn pop(&mut self) -> Option<CreepTarget> {
+        self.tasks.pop_front()
+    }
+    pub fn peek(&self) -> Option<&CreepTarget> {
+        self.tasks.front()
+    }
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.tasks.clear();
+    }
+}
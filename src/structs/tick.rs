@@ -0,0 +1,100 @@
+// a value that only holds for one game tick, like the counts or the requests of this tick. asking
+// for it in a later tick starts over from a fresh one
+#[derive(Debug, Clone)]
+pub struct PerTick<T> {
+    time: u32,
+    value: Option<T>,
+}
+
+impl<T> Default for PerTick<T> {
+    fn default() -> Self {
+        PerTick {
+            time: 0,
+            value: None,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<T> PerTick<T> {
+    // the value of tick `time`, built by `build` when the one kept is from an earlier tick
+    pub fn get_or_insert_with<F>(&mut self, time: u32, build: F) -> &mut T
+    where
+        F: FnOnce() -> T,
+    {
+        if self.time != time {
+            self.value = None;
+        }
+        self.time = time;
+        self.value.get_or_insert_with(build)
+    }
+    // the value when it is from tick `time`
+    pub fn get(&self, time: u32) -> Option<&T> {
+        match self.time == time {
+            true => self.value.as_ref(),
+            false => None,
+        }
+    }
+    // takes the value of tick `time` out, the rest of the tick starts from a fresh one
+    pub fn take(&mut self, time: u32) -> Option<T> {
+        match self.time == time {
+            true => self.value.take(),
+            false => None,
+        }
+    }
+    pub fn is_current(&self, time: u32) -> bool {
+        self.get(time).is_some()
+    }
+    // the value kept, whatever tick it is from
+    pub fn last(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+}
+
+impl<T: Default> PerTick<T> {
+    // the value of tick `time`, a default one when the one kept is from an earlier tick
+    pub fn current(&mut self, time: u32) -> &mut T {
+        self.get_or_insert_with(time, T::default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_lasts_one_tick() {
+        let mut counts: PerTick<u32> = PerTick::default();
+        assert_eq!(counts.get(0), None);
+        *counts.current(10) += 1;
+        *counts.current(10) += 1;
+        assert_eq!(counts.get(10), Some(&2));
+        assert_eq!(counts.get(11), None);
+        assert_eq!(counts.last(), Some(&2));
+        assert_eq!(*counts.current(11), 0);
+    }
+
+    #[test]
+    fn build_only_once_a_tick() {
+        let mut built = 0;
+        let mut value = PerTick::default();
+        for time in [5, 5, 6] {
+            value.get_or_insert_with(time, || {
+                built += 1;
+                time
+            });
+        }
+        assert_eq!(built, 2);
+        assert_eq!(value.get(6), Some(&6));
+    }
+
+    #[test]
+    fn take_leaves_a_fresh_value() {
+        let mut requests: PerTick<Vec<u32>> = PerTick::default();
+        requests.current(3).push(1);
+        assert_eq!(requests.take(4), None);
+        assert_eq!(requests.take(3), Some(vec![1]));
+        assert!(!requests.is_current(3));
+        assert!(requests.current(3).is_empty());
+    }
+}
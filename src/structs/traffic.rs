@@ -0,0 +1,17 @@
+use screeps::Position;
+
+// a step a creep wants to take this tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveRequest {
+    pub from: Position,
+    pub to: Position,
+    pub priority: u32,
+}
+
+// how far the resolver got with a creep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveState {
+    Resolving,
+    Moved,
+    Stayed,
+}
@@ -2,13 +2,16 @@ use anyhow::anyhow;
 use gloo_utils::format::JsValueSerdeExt;
 use log::{error, trace, warn};
 use screeps::{
-    find, game, CircleStyle, ConstructionSite, HasId, HasPosition, ObjectId,
-    OwnedStructureProperties, RectStyle, Room, RoomVisual, Source, StructureController, TextStyle,
+    find, game, CircleStyle, ConstructionSite, HasId, HasPosition, LineStyle, ObjectId,
+    OwnedStructureProperties, Position, RawObjectId, RectStyle, Room, RoomVisual, Source,
+    StructureController, TextStyle,
 };
 use wasm_bindgen::JsValue;
 
 use super::{memory::RoomMemory, room::RoomExtend, source::SourceExtend};
+use crate::managment::reservation::with_ledger;
 pub trait VisualExtend {
+    #[allow(clippy::too_many_arguments)]
     fn draw_progress_bar(
         self,
         x: f32,
@@ -28,19 +31,14 @@ impl RoomExtend for Room {
     }
     fn get_memory_obj(self) -> anyhow::Result<RoomMemory, anyhow::Error> {
         match self.memory().into_serde() {
-            Err(e) => {
-                return Err(anyhow!(
-                    "could not convert jsvalue to room memory struct{e}"
-                ))
-            }
+            Err(e) => Err(anyhow!(
+                "could not convert jsvalue to room memory struct{e}"
+            )),
             Ok(o) => Ok(o),
         }
     }
     fn get_controller_id(&self) -> Option<ObjectId<StructureController>> {
-        match self.controller() {
-            Some(s) => Some(s.id()),
-            None => None,
-        }
+        self.controller().map(|s| s.id())
     }
     fn get_spawn(self) -> Vec<screeps::StructureSpawn> {
         self.find(find::MY_SPAWNS, None)
@@ -64,36 +62,37 @@ impl RoomExtend for Room {
             Ok(o) => {
                 Self::set_memory(&self, &o);
 
-                return Ok(());
+                Ok(())
             }
             Err(e) => {
                 error!("error serializing JsValue to CreepMemory: {}", e);
-                return Err(anyhow!("error serializing JsValue to CreepMemory: {}", e));
+                Err(anyhow!("error serializing JsValue to CreepMemory: {}", e))
             }
         }
     }
-    fn get_best_source(&self) -> Option<Source> {
-        let room_sources = self.clone().get_sources();
-        let mut max_slots = 0;
-        let mut best_source_id: Option<Source> = None;
-
-        for source in room_sources.iter() {
-            let slots = <screeps::Source as Clone>::clone(&source)
-                .get_free_slots()
-                .len();
-            if slots > max_slots {
-                max_slots = slots;
-                best_source_id = Some(source.clone()); // Assuming `.id()` returns a unique identifier for the source
-            }
+    // the source with the most harvest slots nobody has reserved, ties go to the one with the
+    // most energy left. sources in `skip` are left out
+    fn get_best_source(&self, skip: &[RawObjectId]) -> Option<Source> {
+        let best = with_ledger(|ledger| {
+            self.clone()
+                .get_sources()
+                .into_iter()
+                .filter(|source| !skip.contains(&source.id().into()))
+                .max_by_key(|source| {
+                    let slots = source.clone().get_walkable_slots().len() as i64;
+                    let reserved = ledger.get(source.id().into()).creeps as i64;
+                    (slots - reserved, source.energy())
+                })
+        });
+        if let Some(source) = &best {
+            let style = CircleStyle::default().fill("blue");
+            self.visual().circle(
+                source.pos().x().0 as f32,
+                source.pos().y().0 as f32,
+                Some(style),
+            );
         }
-        let style = CircleStyle::default().fill("blue");
-        self.visual().circle(
-            best_source_id.clone().unwrap().pos().x().0 as f32,
-            best_source_id.clone().unwrap().pos().y().0 as f32,
-            Some(style),
-        );
-
-        best_source_id
+        best
     }
 }
 
@@ -141,10 +140,11 @@ pub fn draw_ui(room: &Room) {
     };
     let spawns = &room.clone().get_spawn();
     match spawns.first() {
-        Some(spawn) => match spawn.spawning() {
-            Some(s) => room.visual().text(1.0, 1.0, format!("{}", s.name()), None),
-            None => {}
-        },
+        Some(spawn) => {
+            if let Some(s) = spawn.spawning() {
+                room.visual().text(1.0, 1.0, format!("{}", s.name()), None)
+            }
+        }
         None => {
             warn!("no spawns in room: {}", &room.name())
         }
@@ -170,3 +170,13 @@ pub fn draw_energy(source: &Source, room: &Room) {
             .circle(i.pos().x().0 as f32, i.pos().y().0 as f32, None)
     }
 }
+
+// line from a tower to whatever it is shooting, healing or repairing this tick
+pub fn draw_tower_action(room: &Room, from: Position, to: Position, color: &str) {
+    let style = LineStyle::default().color(color).width(0.15).opacity(0.6);
+    room.visual().line(
+        (from.x().0 as f32, from.y().0 as f32),
+        (to.x().0 as f32, to.y().0 as f32),
+        Some(style),
+    );
+}